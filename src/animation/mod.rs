@@ -0,0 +1,269 @@
+use graphics::types::Rectangle;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+/// How an animation gets from start to finish. Each curve maps how far along in time the
+/// animation is (from 0 to 1) onto how far along it is in distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Starts off quickly and settles gently into place, which is what TV menus tend to do.
+    EaseOut,
+    /// Starts off slowly, speeds up through the middle and settles gently into place.
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Easing, String> {
+        match s {
+            "linear" => Ok(Easing::Linear),
+            "ease-out" => Ok(Easing::EaseOut),
+            "ease-in-out" => Ok(Easing::EaseInOut),
+            _ => Err(format!(
+                "{:?} is not one of linear, ease-out or ease-in-out",
+                s
+            )),
+        }
+    }
+}
+
+/// How long the carousel takes to slide into place, and the curve it follows on the way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// How long an animation takes from start to finish. Zero turns animations off.
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            duration: Duration::from_millis(250),
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
+/// Anything that can be blended between two values.
+pub trait Lerp: Copy + PartialEq {
+    /// Where it is at the given fraction of the way from self to the other value.
+    fn lerp(self, to: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(self, to: f64, t: f64) -> f64 {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Rectangle {
+    fn lerp(self, to: Rectangle, t: f64) -> Rectangle {
+        [
+            self[0].lerp(to[0], t),
+            self[1].lerp(to[1], t),
+            self[2].lerp(to[2], t),
+            self[3].lerp(to[3], t),
+        ]
+    }
+}
+
+/// A value on its way from one place to another over time. Time only moves along when told
+/// to, which the event loop does on every update event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    /// In seconds, which is what piston hands out.
+    elapsed: f64,
+    settings: Settings,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// A tween that is already at rest at the given value.
+    pub fn new(value: T, settings: Settings) -> Tween<T> {
+        Tween {
+            from: value,
+            to: value,
+            elapsed: settings.duration.as_secs_f64(),
+            settings,
+        }
+    }
+
+    /// Heads off towards the given value from wherever the tween is at right now, which may well
+    /// be partway to somewhere else. Heading where it is already headed changes nothing.
+    pub fn retarget(&mut self, to: T) {
+        if to != self.to {
+            self.from = self.value();
+            self.to = to;
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt;
+    }
+
+    pub fn value(&self) -> T {
+        let duration = self.settings.duration.as_secs_f64();
+        if self.done() || duration <= 0.0 {
+            return self.to;
+        }
+        let t = self.settings.easing.apply(self.elapsed / duration);
+        self.from.lerp(self.to, t)
+    }
+
+    pub fn done(&self) -> bool {
+        self.elapsed >= self.settings.duration.as_secs_f64()
+    }
+}
+
+/// A set of tweens, one for each of whatever is being animated (such as the tiles in the
+/// carousel, keyed by game).
+pub struct Tweens<K, T> {
+    settings: Settings,
+    tweens: HashMap<K, Tween<T>>,
+}
+
+impl<K: Eq + Hash, T: Lerp> Tweens<K, T> {
+    pub fn new(settings: Settings) -> Tweens<K, T> {
+        Tweens {
+            settings,
+            tweens: HashMap::new(),
+        }
+    }
+
+    /// Sends the given key's tween on its way to the target and returns where it is at right
+    /// now. Keys that are new start out from wherever the given function says to.
+    pub fn animate<F: FnOnce() -> T>(&mut self, key: K, target: T, start: F) -> T {
+        let settings = self.settings;
+        let tween = self
+            .tweens
+            .entry(key)
+            .or_insert_with(|| Tween::new(start(), settings));
+        tween.retarget(target);
+        tween.value()
+    }
+
+    /// Forgets every key other than the given ones, so that anything that comes back later on
+    /// starts out fresh.
+    pub fn retain(&mut self, keys: &[K]) {
+        self.tweens.retain(|key, _| keys.contains(key));
+    }
+
+    /// Forgets every key.
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for tween in self.tweens.values_mut() {
+            tween.update(dt);
+        }
+    }
+
+    /// Whether anything is still on the move.
+    pub fn animating(&self) -> bool {
+        self.tweens.values().any(|tween| !tween.done())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn easing_curves() {
+        for &easing in &[Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert!(close(easing.apply(0.0), 0.0));
+            assert!(close(easing.apply(1.0), 1.0));
+            // Out of range times are held at either end.
+            assert!(close(easing.apply(-1.0), 0.0));
+            assert!(close(easing.apply(2.0), 1.0));
+            // Never going backwards along the way.
+            let steps: Vec<f64> = (0..=100).map(|t| easing.apply(t as f64 / 100.0)).collect();
+            assert!(
+                steps.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{:?}",
+                easing
+            );
+        }
+        assert!(close(Easing::Linear.apply(0.25), 0.25));
+        assert!(close(Easing::EaseOut.apply(0.5), 0.875));
+        assert!(close(Easing::EaseInOut.apply(0.5), 0.5));
+        assert!(close(Easing::EaseInOut.apply(0.25), 0.0625));
+        assert_eq!("ease-out".parse(), Ok(Easing::EaseOut));
+        assert!("bouncy".parse::<Easing>().is_err());
+    }
+
+    #[test]
+    fn tweens_over_time() {
+        let settings = Settings {
+            duration: Duration::from_secs(1),
+            easing: Easing::Linear,
+        };
+        let mut tween = Tween::new([0.0, 0.0, 320.0, 180.0], settings);
+        assert!(tween.done());
+        tween.retarget([100.0, 0.0, 480.0, 270.0]);
+        assert_eq!(tween.value(), [0.0, 0.0, 320.0, 180.0]);
+        tween.update(0.5);
+        assert_eq!(tween.value(), [50.0, 0.0, 400.0, 225.0]);
+        // Heading somewhere else partway through carries on from where it's at.
+        tween.retarget([0.0, 0.0, 480.0, 270.0]);
+        tween.retarget([0.0, 0.0, 480.0, 270.0]);
+        tween.update(0.5);
+        assert_eq!(tween.value(), [25.0, 0.0, 440.0, 247.5]);
+        tween.update(10.0);
+        assert!(tween.done());
+        assert_eq!(tween.value(), [0.0, 0.0, 480.0, 270.0]);
+        // Without a duration, everything is already where it's going.
+        let mut instant = Tween::new(
+            0.0,
+            Settings {
+                duration: Duration::from_secs(0),
+                ..settings
+            },
+        );
+        instant.retarget(1.0);
+        assert!(instant.done());
+        assert_eq!(instant.value(), 1.0);
+    }
+
+    #[test]
+    fn keyed_tweens() {
+        let mut tweens = Tweens::new(Settings {
+            duration: Duration::from_secs(1),
+            easing: Easing::Linear,
+        });
+        // New keys start out wherever they're told to.
+        assert_eq!(tweens.animate(1, 10.0, || 0.0), 0.0);
+        assert!(tweens.animating());
+        tweens.update(0.5);
+        assert_eq!(tweens.animate(1, 10.0, || unreachable!()), 5.0);
+        tweens.update(0.5);
+        assert_eq!(tweens.animate(1, 10.0, || unreachable!()), 10.0);
+        assert!(!tweens.animating());
+        tweens.retain(&[2]);
+        assert_eq!(tweens.animate(1, 20.0, || 20.0), 20.0);
+        tweens.clear();
+        assert!(!tweens.animating());
+    }
+}
@@ -1,14 +1,169 @@
+use crate::client::Client;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use std::fmt::Formatter;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 
 #[derive(Deserialize)]
 pub struct Schedule {
+    #[allow(dead_code)]
     pub copyright: String,
     pub dates: Vec<Date>,
 }
 
-pub static DEFAULT: &str = "http://statsapi.mlb.com/api/v1/schedule?hydrate=\
-    game(content(editorial(recap))),decisions&date=2018-06-10&sportId=1";
+static STATSAPI: &str = "https://statsapi.mlb.com";
+static SCHEDULE_PATH: &str = "/api/v1/schedule";
+
+/// The sportId that statsapi uses for Major League Baseball.
+pub static MLB: u32 = 1;
+
+/// A typed description of a request against the statsapi schedule endpoint.
+///
+/// The defaults mirror what this application needs in order to render its lineup (that is,
+/// MLB games hydrated with their editorial recaps, decisions and full team details) so most
+/// callers only ever have to pick a date. E.G.
+///
+/// ```
+/// let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 10).unwrap()).team(147);
+/// Schedule::try_from(&Client::default(), query.url()).await?;
+/// ```
+///
+/// A query covers a single day unless it is given a later day to run through.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleQuery {
+    date: NaiveDate,
+    end: NaiveDate,
+    sport_id: u32,
+    hydrate: Vec<String>,
+    team_id: Option<u32>,
+}
+
+impl ScheduleQuery {
+    pub fn new(date: NaiveDate) -> ScheduleQuery {
+        ScheduleQuery {
+            date,
+            end: date,
+            sport_id: MLB,
+            hydrate: vec![],
+            team_id: None,
+        }
+        .hydrate("game(content(editorial(recap)))")
+        .hydrate("decisions")
+        // Without this, teams come back with little more than their names.
+        .hydrate("team")
+        // Which inning a game in progress is in.
+        .hydrate("linescore")
+    }
+
+    /// The first (and usually only) day of the query.
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// The last day of the query, which is the same as the first unless it covers a range.
+    pub fn end_date(&self) -> NaiveDate {
+        self.end
+    }
+
+    /// Whether the given day is one of the days that the query covers.
+    pub fn includes(&self, date: NaiveDate) -> bool {
+        self.date <= date && date <= self.end
+    }
+
+    /// Extends the query to run from its date through to the given one (inclusive). A day before
+    /// the query's date leaves it at the one day.
+    pub fn through(mut self, end: NaiveDate) -> ScheduleQuery {
+        self.end = end.max(self.date);
+        self
+    }
+
+    /// The same query, but for the day after. A query over a range of days moves on by the
+    /// whole range, so that flipping through them never shows the same day twice.
+    pub fn next_day(&self) -> ScheduleQuery {
+        self.shift(self.days())
+    }
+
+    /// The same query, but for the day (or range of days) before.
+    pub fn previous_day(&self) -> ScheduleQuery {
+        self.shift(-self.days())
+    }
+
+    fn days(&self) -> i64 {
+        (self.end - self.date).num_days() + 1
+    }
+
+    fn shift(&self, days: i64) -> ScheduleQuery {
+        let by = chrono::Duration::days(days);
+        match (
+            self.date.checked_add_signed(by),
+            self.end.checked_add_signed(by),
+        ) {
+            (Some(date), Some(end)) => ScheduleQuery {
+                date,
+                end,
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    pub fn sport(mut self, sport_id: u32) -> ScheduleQuery {
+        self.sport_id = sport_id;
+        self
+    }
+
+    /// Appends an item to the hydrate list. Duplicates are ignored.
+    pub fn hydrate<T: Into<String>>(mut self, item: T) -> ScheduleQuery {
+        let item = item.into();
+        if !self.hydrate.contains(&item) {
+            self.hydrate.push(item);
+        }
+        self
+    }
+
+    /// Restricts the schedule to games in which the given team is playing.
+    pub fn team(mut self, team_id: u32) -> ScheduleQuery {
+        self.team_id = Some(team_id);
+        self
+    }
+
+    pub fn url(&self) -> String {
+        self.url_at(&format!("{}{}", STATSAPI, SCHEDULE_PATH))
+    }
+
+    /// The URL of this query against a schedule endpoint other than statsapi's.
+    pub fn url_at(&self, endpoint: &str) -> String {
+        let dates = if self.end == self.date {
+            format!("date={}", self.date.format("%Y-%m-%d"))
+        } else {
+            format!(
+                "startDate={}&endDate={}",
+                self.date.format("%Y-%m-%d"),
+                self.end.format("%Y-%m-%d")
+            )
+        };
+        let mut url = format!(
+            "{}?hydrate={}&{}&sportId={}",
+            endpoint,
+            self.hydrate.join(","),
+            dates,
+            self.sport_id
+        );
+        if let Some(team_id) = self.team_id {
+            url.push_str(&format!("&teamId={}", team_id));
+        }
+        url
+    }
+}
+
+impl std::fmt::Display for ScheduleQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(&self.url())
+    }
+}
 
 impl Schedule {
     /// I do not believe that there is an async version of std::convert provided by anyone.
@@ -24,9 +179,9 @@ impl Schedule {
     ///
     ///  ```
     /// let url = "https://black.coffee".to_string();
-    /// Schedule::try_from(&url).unwrap(); // as a borrow
-    /// Schedule::try_from(url).unwrap(); // as a move
-    /// Schedule::try_from("https://backflip.gov").unwrap(); // as a str literal, etc.
+    /// Schedule::try_from(&client, &url).unwrap(); // as a borrow
+    /// Schedule::try_from(&client, url).unwrap(); // as a move
+    /// Schedule::try_from(&client, "https://backflip.gov").unwrap(); // as a str literal, etc.
     /// ```
     ///
     /// Of course, the tradeoffs of monomorphization versus taking in a dynamic trait is whether
@@ -34,87 +189,402 @@ impl Schedule {
     /// will get code generated for every different way that it is called in the target binary
     /// which increases the raw size of the binary. Alternatively, a Box::<dyn trait> incurs
     /// the wrath of a fat pointer with a dynamic lookup to the concrete type. Pick your poison.
-    pub async fn try_from<T: AsRef<str>>(src: T) -> APIResult<Schedule> {
-        let target = src.as_ref().parse::<hyper::Uri>().map_err(|err| APIError {
-            src: src.as_ref().to_string(),
-            context: ErrorContext::URIParsing,
-            original: err.to_string(),
-        })?;
-        let resp = hyper::Client::default()
-            .get(target)
-            .await
-            .map_err(|err| APIError {
-                src: src.as_ref().to_string(),
-                context: ErrorContext::ConnectionEstablishment,
-                original: err.to_string(),
-            })?;
-        let buf = hyper::body::to_bytes(resp).await.map_err(|err| APIError {
-            src: src.as_ref().to_string(),
-            context: ErrorContext::Downloading,
-            original: err.to_string(),
-        })?;
-        serde_json::from_slice(&buf).map_err(|err| APIError {
-            src: src.as_ref().to_string(),
-            context: ErrorContext::Deserializing,
-            original: err.to_string(),
+    ///
+    /// The connection itself comes out of the given client's pool.
+    pub async fn try_from<T: AsRef<str>>(client: &Client, src: T) -> APIResult<Schedule> {
+        get_json(client, src.as_ref()).await
+    }
+
+    /// Reads a schedule response that was previously saved to disk.
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> APIResult<Schedule> {
+        read_json(path.as_ref()).await
+    }
+}
+
+impl Feed {
+    pub async fn try_from<T: AsRef<str>>(client: &Client, src: T) -> APIResult<Feed> {
+        get_json(client, src.as_ref()).await
+    }
+
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> APIResult<Feed> {
+        read_json(path.as_ref()).await
+    }
+}
+
+impl Linescore {
+    pub async fn try_from<T: AsRef<str>>(client: &Client, src: T) -> APIResult<Linescore> {
+        get_json(client, src.as_ref()).await
+    }
+
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> APIResult<Linescore> {
+        read_json(path.as_ref()).await
+    }
+}
+
+async fn get_json<T: DeserializeOwned>(client: &Client, src: &str) -> APIResult<T> {
+    let resp = client.get(src).await?;
+    serde_json::from_slice(&resp.body).map_err(|err| APIError {
+        src: src.to_string(),
+        context: ErrorContext::Deserializing,
+        original: err.to_string(),
+    })
+}
+
+async fn read_json<T: DeserializeOwned>(path: &Path) -> APIResult<T> {
+    let src = path.display().to_string();
+    let buf = tokio::fs::read(path)
+        .await
+        .map_err(|err| APIError::new(&src, ErrorContext::Reading, err))?;
+    serde_json::from_slice(&buf)
+        .map_err(|err| APIError::new(&src, ErrorContext::Deserializing, err))
+}
+
+/// Something that is on its way in from a ScheduleSource, a schedule unless said otherwise.
+pub type Fetch<T = Schedule> = Pin<Box<dyn Future<Output = APIResult<T>> + Send>>;
+
+/// Where schedules, and everything else about the games on them, come from.
+///
+/// Everything above the api module asks for its schedules through this trait rather than
+/// going to statsapi directly, so that the whole pipeline can be pointed at a saved response
+/// or at a stand-in server.
+pub trait ScheduleSource: Send + Sync {
+    fn fetch(&self, query: &ScheduleQuery) -> Fetch;
+
+    /// The live feed of a single game, which has everything there is to know about it.
+    fn feed(&self, game_pk: u64) -> Fetch<Feed>;
+
+    /// The linescore of a single game, which is a whole lot lighter than its feed.
+    fn linescore(&self, game_pk: u64) -> Fetch<Linescore>;
+}
+
+/// Fetches schedules over HTTP, from statsapi by default.
+pub struct HttpSource {
+    client: Client,
+    host: String,
+}
+
+impl HttpSource {
+    pub fn new(client: Client) -> HttpSource {
+        HttpSource::at(client, STATSAPI)
+    }
+
+    /// Fetches schedules from a host that speaks the same language as statsapi, E.G.
+    /// "http://localhost:8080".
+    pub fn at<T: Into<String>>(client: Client, host: T) -> HttpSource {
+        HttpSource {
+            client,
+            host: host.into(),
+        }
+    }
+}
+
+impl ScheduleSource for HttpSource {
+    fn fetch(&self, query: &ScheduleQuery) -> Fetch {
+        let client = self.client.clone();
+        let url = query.url_at(&format!("{}{}", self.host, SCHEDULE_PATH));
+        Box::pin(async move { Schedule::try_from(&client, url).await })
+    }
+
+    fn feed(&self, game_pk: u64) -> Fetch<Feed> {
+        let client = self.client.clone();
+        let url = format!("{}/api/v1.1/game/{}/feed/live", self.host, game_pk);
+        Box::pin(async move { Feed::try_from(&client, url).await })
+    }
+
+    fn linescore(&self, game_pk: u64) -> Fetch<Linescore> {
+        let client = self.client.clone();
+        let url = format!("{}/api/v1/game/{}/linescore", self.host, game_pk);
+        Box::pin(async move { Linescore::try_from(&client, url).await })
+    }
+}
+
+/// Reads a schedule response that was saved to disk. The same schedule is served up no
+/// matter which day is asked for.
+///
+/// The feed and linescore of each game are read from files named after it (E.G.
+/// feed_530781.json and linescore_530781.json) that sit in the same directory as the schedule.
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileSource {
+        FileSource { path: path.into() }
+    }
+}
+
+impl ScheduleSource for FileSource {
+    /// Only the days that the query covers are kept, the same as statsapi would, so that going
+    /// from one day to the next actually goes somewhere.
+    fn fetch(&self, query: &ScheduleQuery) -> Fetch {
+        let (path, query) = (self.path.clone(), query.clone());
+        Box::pin(async move {
+            let mut schedule = Schedule::from_file(path).await?;
+            schedule.dates.retain(|date| query.includes(date.date));
+            Ok(schedule)
         })
     }
+
+    fn feed(&self, game_pk: u64) -> Fetch<Feed> {
+        let path = self.path.with_file_name(format!("feed_{}.json", game_pk));
+        Box::pin(Feed::from_file(path))
+    }
+
+    fn linescore(&self, game_pk: u64) -> Fetch<Linescore> {
+        let path = self
+            .path
+            .with_file_name(format!("linescore_{}.json", game_pk));
+        Box::pin(Linescore::from_file(path))
+    }
 }
 
 #[derive(Deserialize)]
 pub struct Date {
-    pub date: String,
+    pub date: NaiveDate,
     pub games: Vec<Game>,
 }
 
+/// Editorial content only shows up once somebody has gotten around to writing it, so games that
+/// haven't been played yet (or were postponed) tend to be missing some or all of it. Anything
+/// along that path is optional so that one such game doesn't sink the whole schedule.
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Game {
-    pub content: Content,
+    pub game_pk: u64,
+    /// When the game is scheduled to start, which isn't necessarily when it did start.
+    pub game_date: DateTime<Utc>,
+    pub status: Status,
+    pub teams: Teams,
+    pub venue: Option<Venue>,
+    pub linescore: Option<Linescore>,
+    /// Only present once the game is over (and not even always then).
+    pub decisions: Option<Decisions>,
+    pub content: Option<Content>,
+}
+
+impl Game {
+    pub fn recap(&self) -> Option<&Home> {
+        self.content
+            .as_ref()?
+            .editorial
+            .as_ref()?
+            .recap
+            .as_ref()?
+            .home
+            .as_ref()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Teams {
+    pub away: Side,
+    pub home: Side,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Side {
+    pub team: Team,
+    /// Only present once the game has started.
+    pub score: Option<u32>,
+    pub is_winner: Option<bool>,
+    pub league_record: Option<Record>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// The abbreviation and short name only come along when the "team" hydration is asked for.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Team {
+    pub name: String,
+    pub abbreviation: Option<String>,
+    pub team_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    /// One of "Preview", "Live" or "Final".
+    pub abstract_game_state: String,
+    /// Something more human friendly, such as "In Progress", "Postponed" or "Delayed Start".
+    pub detailed_state: String,
+    /// Why the game was postponed, delayed, suspended and the like.
+    pub reason: Option<String>,
+}
+
+/// The pitchers of record.
+#[derive(Deserialize)]
+pub struct Decisions {
+    pub winner: Option<Person>,
+    pub loser: Option<Person>,
+    pub save: Option<Person>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Person {
+    pub full_name: String,
+}
+
+/// Where a game is at. The schedule leaves out the innings themselves, but the feed and the
+/// linescore endpoint have them.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Linescore {
+    pub current_inning: Option<u32>,
+    /// E.G. "7th"
+    pub current_inning_ordinal: Option<String>,
+    /// One of "Top", "Middle", "Bottom" or "End".
+    pub inning_state: Option<String>,
+    pub scheduled_innings: Option<u32>,
+    /// Only the innings that have gotten underway.
+    #[serde(default)]
+    pub innings: Vec<Inning>,
+    /// The totals for the game so far.
+    pub teams: Option<Halves>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inning {
+    pub num: u32,
+    pub away: Line,
+    pub home: Line,
+}
+
+#[derive(Deserialize)]
+pub struct Halves {
+    pub away: Line,
+    pub home: Line,
+}
+
+/// One team's half of an inning, or of the whole game.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Line {
+    /// Missing for a half inning that hasn't been (or never will be) played, such as the bottom
+    /// of the ninth when the home team is already ahead.
+    pub runs: Option<u32>,
+    #[serde(default)]
+    pub hits: u32,
+    #[serde(default)]
+    pub errors: u32,
+}
+
+/// Everything that there is to know about a single game, from its live feed. Only the parts
+/// that the detail view needs are picked out.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+    pub live_data: LiveData,
+}
+
+#[derive(Deserialize)]
+pub struct LiveData {
+    pub linescore: Option<Linescore>,
+    pub boxscore: Option<Boxscore>,
+    pub decisions: Option<Decisions>,
+}
+
+#[derive(Deserialize)]
+pub struct Boxscore {
+    pub teams: BoxscoreTeams,
+    /// The odds and ends at the bottom of a box score, such as the weather and attendance.
+    #[serde(default)]
+    pub info: Vec<BoxscoreInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct BoxscoreTeams {
+    pub away: BoxscoreTeam,
+    pub home: BoxscoreTeam,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxscoreTeam {
+    pub team_stats: TeamStats,
+}
+
+#[derive(Deserialize)]
+pub struct TeamStats {
+    pub batting: Batting,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Batting {
+    pub runs: u32,
+    pub hits: u32,
+    pub home_runs: u32,
+    pub strike_outs: u32,
+    pub base_on_balls: u32,
+    pub left_on_base: u32,
+}
+
+#[derive(Deserialize)]
+pub struct BoxscoreInfo {
+    pub label: String,
+    /// Missing for headings, such as the date of the game.
+    pub value: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Venue {
+    pub name: String,
 }
 
 #[derive(Deserialize)]
 pub struct Content {
-    pub editorial: Editorial,
+    pub editorial: Option<Editorial>,
 }
 
 #[derive(Deserialize)]
 pub struct Editorial {
-    pub recap: Recap,
+    pub recap: Option<Recap>,
 }
 
 #[derive(Deserialize)]
 pub struct Recap {
-    pub home: Home,
+    pub home: Option<Home>,
 }
 
 #[derive(Deserialize)]
 pub struct Home {
-    pub headline: String,
+    pub headline: Option<String>,
+    #[serde(default)]
     pub subhead: String,
-    pub photo: Photos,
+    /// The recap itself, as HTML.
+    #[serde(default)]
+    pub body: String,
+    pub photo: Option<Photos>,
 }
 
 #[derive(Deserialize)]
 pub struct Photos {
+    #[serde(default)]
     pub cuts: Cuts,
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 pub struct Cuts {
     #[serde(alias = "480x270")]
-    pub large: Photo,
+    pub large: Option<Photo>,
     #[serde(alias = "320x180")]
-    pub small: Photo,
+    pub small: Option<Photo>,
 }
 
 #[derive(Deserialize)]
 pub struct Photo {
-    pub width: u32,
-    pub height: u32,
-    pub src: String,
+    pub src: Option<String>,
 }
 
-type APIResult<T> = Result<T, APIError>;
+pub type APIResult<T> = Result<T, APIError>;
 
 pub struct APIError {
     src: String,
@@ -122,6 +592,25 @@ pub struct APIError {
     original: String,
 }
 
+impl APIError {
+    pub fn new<T: AsRef<str>, E: ToString>(src: T, context: ErrorContext, original: E) -> APIError {
+        APIError {
+            src: src.as_ref().to_string(),
+            context,
+            original: original.to_string(),
+        }
+    }
+
+    /// The given source didn't come through within the given amount of time.
+    pub fn timeout<T: AsRef<str>>(src: T, after: std::time::Duration) -> APIError {
+        APIError::new(
+            src,
+            ErrorContext::Timeout,
+            format!("gave up after {} seconds", after.as_secs_f64()),
+        )
+    }
+}
+
 impl std::error::Error for APIError {}
 
 impl std::fmt::Display for APIError {
@@ -144,6 +633,9 @@ pub enum ErrorContext {
     ConnectionEstablishment,
     Downloading,
     Deserializing,
+    Decoding,
+    Reading,
+    Timeout,
 }
 
 impl std::fmt::Display for ErrorContext {
@@ -157,24 +649,353 @@ impl std::fmt::Display for ErrorContext {
             Self::Deserializing => {
                 f.write_str("Failed to deserialize data from the given API endpoint")
             }
+            Self::Decoding => f.write_str("Failed to decode the image from the given endpoint"),
+            Self::Reading => f.write_str("Failed to read the given file"),
+            Self::Timeout => f.write_str("Timed out waiting on the given API endpoint"),
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub mod testing {
     use super::*;
+    use std::collections::HashMap;
+
+    /// Serves schedule responses straight out of memory.
+    ///
+    /// Responses can be registered for particular days, and anything else gets the fallback
+    /// response (if there is one). Feeds are registered per game. The responses are kept as raw
+    /// JSON so that deserialization gets exercised just the same as it would be for a live
+    /// response.
+    #[derive(Default)]
+    pub struct FixtureSource {
+        days: HashMap<NaiveDate, Vec<u8>>,
+        fallback: Option<Vec<u8>>,
+        feeds: HashMap<u64, Vec<u8>>,
+        linescores: HashMap<u64, Vec<u8>>,
+    }
+
+    impl FixtureSource {
+        pub fn new<T: Into<Vec<u8>>>(fallback: T) -> FixtureSource {
+            FixtureSource {
+                fallback: Some(fallback.into()),
+                ..FixtureSource::default()
+            }
+        }
+
+        pub fn on<T: Into<Vec<u8>>>(mut self, date: NaiveDate, response: T) -> FixtureSource {
+            self.days.insert(date, response.into());
+            self
+        }
 
-    static TEST_DATA: &[u8] = include_bytes!("test.json");
+        pub fn with_feed<T: Into<Vec<u8>>>(mut self, game_pk: u64, feed: T) -> FixtureSource {
+            self.feeds.insert(game_pk, feed.into());
+            self
+        }
+
+        pub fn with_linescore<T: Into<Vec<u8>>>(
+            mut self,
+            game_pk: u64,
+            linescore: T,
+        ) -> FixtureSource {
+            self.linescores.insert(game_pk, linescore.into());
+            self
+        }
+    }
+
+    impl ScheduleSource for FixtureSource {
+        fn fetch(&self, query: &ScheduleQuery) -> Fetch {
+            let src = format!("fixture:{}", query.date());
+            let result = match self.days.get(&query.date()).or(self.fallback.as_ref()) {
+                Some(response) => serde_json::from_slice(response)
+                    .map_err(|err| APIError::new(&src, ErrorContext::Deserializing, err)),
+                None => Err(APIError::new(
+                    &src,
+                    ErrorContext::Reading,
+                    "no fixture was registered for this day",
+                )),
+            };
+            Box::pin(std::future::ready(result))
+        }
+
+        fn feed(&self, game_pk: u64) -> Fetch<Feed> {
+            Box::pin(std::future::ready(FixtureSource::game(
+                &self.feeds,
+                game_pk,
+            )))
+        }
+
+        fn linescore(&self, game_pk: u64) -> Fetch<Linescore> {
+            Box::pin(std::future::ready(FixtureSource::game(
+                &self.linescores,
+                game_pk,
+            )))
+        }
+    }
+
+    impl FixtureSource {
+        fn game<T: DeserializeOwned>(
+            responses: &HashMap<u64, Vec<u8>>,
+            game_pk: u64,
+        ) -> APIResult<T> {
+            let src = format!("fixture:{}", game_pk);
+            match responses.get(&game_pk) {
+                Some(response) => serde_json::from_slice(response)
+                    .map_err(|err| APIError::new(&src, ErrorContext::Deserializing, err)),
+                None => Err(APIError::new(
+                    &src,
+                    ErrorContext::Reading,
+                    "nothing was registered for this game",
+                )),
+            }
+        }
+    }
+
+    /// The live feed of the first game in TEST_DATA.
+    pub static FEED_DATA: &[u8] = include_bytes!("feed.json");
+
+    /// The linescore of the first game in TEST_DATA.
+    pub static LINESCORE_DATA: &[u8] = include_bytes!("linescore.json");
+
+    /// A schedule response for 2018-06-10, trimmed down to seven games. The last of them was
+    /// postponed and so has no recap.
+    pub static TEST_DATA: &[u8] = include_bytes!("test.json");
+
+    /// Serves the given body in response to every request on an arbitrary local port, standing
+    /// in for statsapi. Returns the host to point an HttpSource at. Must be called from within a
+    /// runtime.
+    pub fn stand_in(body: &'static [u8]) -> String {
+        let make_service = hyper::service::make_service_fn(move |_| async move {
+            Ok::<_, hyper::Error>(hyper::service::service_fn(move |_| async move {
+                Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::from(body)))
+            }))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let host = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        host
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::*;
+    use super::*;
 
     #[test]
     fn smoke() {
-        let _: Schedule = serde_json::from_slice(TEST_DATA).unwrap();
+        let schedule: Schedule = serde_json::from_slice(TEST_DATA).unwrap();
+        let games = &schedule.dates[0].games;
+        assert!(games[0].recap().is_some());
+        assert!(games[6].recap().is_none());
+        assert_eq!(games[6].teams.home.team.name, "New York Yankees");
+        assert_eq!(games[6].status.detailed_state, "Postponed");
+        assert_eq!(games[6].teams.home.score, None);
+    }
+
+    #[test]
+    fn teams_status_and_venue() {
+        let schedule: Schedule = serde_json::from_slice(TEST_DATA).unwrap();
+        let game = &schedule.dates[0].games[0];
+        assert_eq!(game.game_pk, 530781);
+        assert_eq!(game.game_date.to_rfc3339(), "2018-06-10T17:05:00+00:00");
+        assert_eq!(game.status.abstract_game_state, "Final");
+        assert_eq!(game.teams.away.team.abbreviation.as_deref(), Some("TB"));
+        assert_eq!(game.teams.away.score, Some(3));
+        assert_eq!(game.teams.home.team.team_name.as_deref(), Some("Orioles"));
+        assert_eq!(game.teams.home.is_winner, Some(true));
+        assert_eq!(
+            game.teams
+                .home
+                .league_record
+                .as_ref()
+                .map(|r| (r.wins, r.losses)),
+            Some((20, 45))
+        );
+        assert_eq!(
+            game.venue.as_ref().map(|venue| venue.name.as_str()),
+            Some("Oriole Park at Camden Yards")
+        );
+        let decisions = game.decisions.as_ref().unwrap();
+        let name = |person: &Option<Person>| person.as_ref().map(|p| p.full_name.clone());
+        assert_eq!(name(&decisions.winner).as_deref(), Some("Kevin Gausman"));
+        assert_eq!(name(&decisions.loser).as_deref(), Some("Sergio Romo"));
+        assert_eq!(name(&decisions.save).as_deref(), Some("Zach Britton"));
+        assert!(schedule.dates[0].games[6].decisions.is_none());
     }
 
     #[test]
     fn smoke_async() {
         // This just smoke checks that our api call is working.
-        let _: Schedule = tokio::runtime::Runtime::new().unwrap().block_on(Schedule::try_from(DEFAULT)).unwrap();
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 10).unwrap());
+        let _: Schedule = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async {
+                HttpSource::at(Client::default(), stand_in(TEST_DATA))
+                    .fetch(&query)
+                    .await
+            })
+            .unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fixture_source() {
+        let day = NaiveDate::from_ymd_opt(2018, 6, 10).unwrap();
+        let source = FixtureSource::default().on(day, TEST_DATA);
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let schedule = runtime
+            .block_on(source.fetch(&ScheduleQuery::new(day)))
+            .unwrap();
+        assert_eq!(schedule.dates[0].games.len(), 7);
+        assert!(runtime
+            .block_on(source.fetch(&ScheduleQuery::new(day).next_day()))
+            .is_err());
+        let garbage = FixtureSource::new("{");
+        assert!(runtime
+            .block_on(garbage.fetch(&ScheduleQuery::new(day)))
+            .is_err());
+    }
+
+    #[test]
+    fn feed() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let source = FixtureSource::new(TEST_DATA).with_feed(530781, FEED_DATA);
+        let feed = runtime.block_on(source.feed(530781)).unwrap();
+        let linescore = feed.live_data.linescore.unwrap();
+        assert_eq!(linescore.innings.len(), 9);
+        assert_eq!(linescore.innings[8].home.runs, None);
+        let totals = linescore.teams.unwrap();
+        assert_eq!(
+            (totals.away.runs, totals.away.hits, totals.away.errors),
+            (Some(3), 7, 1)
+        );
+        assert_eq!(
+            (totals.home.runs, totals.home.hits, totals.home.errors),
+            (Some(4), 9, 0)
+        );
+        let boxscore = feed.live_data.boxscore.unwrap();
+        assert_eq!(boxscore.teams.home.team_stats.batting.home_runs, 2);
+        assert_eq!(boxscore.info[0].label, "WP");
+        assert!(boxscore.info.last().unwrap().value.is_none());
+        assert!(runtime.block_on(source.feed(1)).is_err());
+        let offline = FileSource::new("src/api/test.json");
+        assert!(runtime.block_on(offline.feed(530781)).is_err());
+    }
+
+    #[test]
+    fn file_source_keeps_to_the_query() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let offline = FileSource::new("src/api/test.json");
+        let day = NaiveDate::from_ymd_opt(2018, 6, 10).unwrap();
+        let query = ScheduleQuery::new(day);
+        let schedule = runtime.block_on(offline.fetch(&query)).unwrap();
+        assert_eq!(schedule.dates.len(), 1);
+        assert_eq!(schedule.dates[0].games.len(), 7);
+        let schedule = runtime.block_on(offline.fetch(&query.next_day())).unwrap();
+        assert!(schedule.dates.is_empty());
+        let schedule = runtime
+            .block_on(offline.fetch(&query.previous_day().through(day)))
+            .unwrap();
+        assert_eq!(schedule.dates.len(), 1);
+    }
+
+    #[test]
+    fn linescore() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let source = FixtureSource::new(TEST_DATA).with_linescore(530781, LINESCORE_DATA);
+        let linescore = runtime.block_on(source.linescore(530781)).unwrap();
+        assert_eq!(linescore.scheduled_innings, Some(9));
+        assert_eq!(
+            linescore
+                .innings
+                .iter()
+                .map(|inning| inning.num)
+                .collect::<Vec<u32>>(),
+            (1..=9).collect::<Vec<u32>>()
+        );
+        assert_eq!(linescore.innings[4].away.runs, Some(2));
+        assert_eq!(linescore.teams.unwrap().home.hits, 9);
+        assert!(runtime.block_on(source.linescore(530782)).is_err());
+    }
+
+    #[test]
+    fn from_file() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 10).unwrap());
+        let schedule = runtime
+            .block_on(FileSource::new("src/api/test.json").fetch(&query))
+            .unwrap();
+        assert_eq!(schedule.dates[0].games.len(), 7);
+        let err = runtime
+            .block_on(Schedule::from_file("src/api/missing.json"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("Failed to read the given file"));
+    }
+
+    #[test]
+    fn default_query() {
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 10).unwrap());
+        assert_eq!(
+            query.url(),
+            "https://statsapi.mlb.com/api/v1/schedule?hydrate=\
+            game(content(editorial(recap))),decisions,team,linescore&date=2018-06-10&sportId=1"
+        );
+    }
+
+    #[test]
+    fn query_day_navigation() {
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 30).unwrap()).team(147);
+        assert_eq!(
+            query.next_day(),
+            ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 7, 1).unwrap()).team(147)
+        );
+        assert_eq!(query.next_day().previous_day(), query);
+        // Ranges move on by the whole range at a time.
+        let week = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 7, 9).unwrap())
+            .through(NaiveDate::from_ymd_opt(2018, 7, 15).unwrap());
+        let next = week.next_day();
+        assert_eq!(next.date(), NaiveDate::from_ymd_opt(2018, 7, 16).unwrap());
+        assert_eq!(
+            next.end_date(),
+            NaiveDate::from_ymd_opt(2018, 7, 22).unwrap()
+        );
+        assert_eq!(next.previous_day(), week);
+    }
+
+    #[test]
+    fn date_range_query() {
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 7, 16).unwrap())
+            .through(NaiveDate::from_ymd_opt(2018, 7, 18).unwrap());
+        assert_eq!(
+            query.url(),
+            "https://statsapi.mlb.com/api/v1/schedule?hydrate=\
+            game(content(editorial(recap))),decisions,team,linescore\
+            &startDate=2018-07-16&endDate=2018-07-18&sportId=1"
+        );
+        let day = |day| NaiveDate::from_ymd_opt(2018, 7, day).unwrap();
+        assert!(query.includes(day(16)) && query.includes(day(18)));
+        assert!(!query.includes(day(15)) && !query.includes(day(19)));
+        // Running through an earlier day makes no sense, so it's the one day after all.
+        let backwards = ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 7, 16).unwrap())
+            .through(NaiveDate::from_ymd_opt(2018, 7, 1).unwrap());
+        assert_eq!(
+            backwards,
+            ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 7, 16).unwrap())
+        );
+    }
+
+    #[test]
+    fn query_builder() {
+        let query = ScheduleQuery::new(NaiveDate::from_ymd_opt(2019, 10, 1).unwrap())
+            .sport(11)
+            .hydrate("linescore")
+            .hydrate("decisions")
+            .team(147);
+        assert_eq!(
+            query.url(),
+            "https://statsapi.mlb.com/api/v1/schedule?hydrate=\
+            game(content(editorial(recap))),decisions,team,linescore&date=2019-10-01&sportId=11&teamId=147"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::api::{APIError, APIResult, ErrorContext};
+use hyper::body::Bytes;
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderValue, CONTENT_TYPE, LOCATION, USER_AGENT};
+use hyper::StatusCode;
+use hyper_tls::HttpsConnector;
+use std::time::Duration;
+
+/// Timeouts, pooling and redirects for the HTTP client that the schedule and photo downloads
+/// share. None of this can be set from the command line, so the defaults are all there is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub user_agent: String,
+    /// How long to wait on a TCP connection to be established.
+    pub connect_timeout: Duration,
+    /// How long an unused connection is kept around in the pool before being closed.
+    pub idle_timeout: Duration,
+    /// The number of unused connections kept around per host.
+    pub max_idle_per_host: usize,
+    /// The number of redirects followed before a request is given up on.
+    pub max_redirects: usize,
+    /// How long a request has to finish in, from connecting through to reading the last of the
+    /// body (and including any redirects along the way).
+    pub request_timeout: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            user_agent: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            connect_timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(90),
+            max_idle_per_host: 8,
+            max_redirects: 5,
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The one HTTP(S) client that everything in the application goes through.
+///
+/// The schedule and every last photo used to each get a client of their own, which meant a
+/// fresh TCP connection (and TLS handshake) per request even though they nearly all go to the
+/// same couple of hosts. Clones of a Client share the same connection pool, so build one up
+/// front and hand out clones.
+#[derive(Clone)]
+pub struct Client {
+    inner: hyper::Client<HttpsConnector<HttpConnector>>,
+    user_agent: HeaderValue,
+    max_redirects: usize,
+    request_timeout: Duration,
+}
+
+/// The parts of a successful response that anyone has cared about so far.
+pub struct Response {
+    pub content_type: Option<String>,
+    pub body: Bytes,
+}
+
+impl Default for Client {
+    fn default() -> Client {
+        Client::new(&Settings::default())
+    }
+}
+
+impl Client {
+    pub fn new(settings: &Settings) -> Client {
+        let mut http = HttpConnector::new();
+        // The HttpsConnector takes care of the https scheme itself.
+        http.enforce_http(false);
+        http.set_connect_timeout(Some(settings.connect_timeout));
+        let inner = hyper::Client::builder()
+            .pool_idle_timeout(settings.idle_timeout)
+            .pool_max_idle_per_host(settings.max_idle_per_host)
+            .build(HttpsConnector::new_with_connector(http));
+        Client {
+            inner,
+            user_agent: HeaderValue::from_str(&settings.user_agent)
+                .unwrap_or_else(|_| HeaderValue::from_static("DDS")),
+            max_redirects: settings.max_redirects,
+            request_timeout: settings.request_timeout,
+        }
+    }
+
+    /// GETs the given URL, following any redirects along the way, and reads in the body.
+    ///
+    /// Anything other than a 2xx at the end of the redirect chain is treated as an error, as
+    /// an error page is never going to deserialize into a schedule or decode into a photo anyway.
+    pub async fn get<T: AsRef<str>>(&self, src: T) -> APIResult<Response> {
+        let src = src.as_ref();
+        tokio::time::timeout(self.request_timeout, self.fetch(src))
+            .await
+            .unwrap_or_else(|_| Err(APIError::timeout(src, self.request_timeout)))
+    }
+
+    async fn fetch(&self, src: &str) -> APIResult<Response> {
+        let mut target = src.to_string();
+        let mut redirects = 0;
+        let resp = loop {
+            let uri = target
+                .parse::<hyper::Uri>()
+                .map_err(|err| APIError::new(&target, ErrorContext::URIParsing, err))?;
+            let req = hyper::Request::get(uri)
+                .header(USER_AGENT, self.user_agent.clone())
+                .body(hyper::Body::empty())
+                .map_err(|err| APIError::new(&target, ErrorContext::URIParsing, err))?;
+            let resp = self.inner.request(req).await.map_err(|err| {
+                APIError::new(&target, ErrorContext::ConnectionEstablishment, err)
+            })?;
+            match resp.status() {
+                StatusCode::MOVED_PERMANENTLY
+                | StatusCode::FOUND
+                | StatusCode::SEE_OTHER
+                | StatusCode::TEMPORARY_REDIRECT
+                | StatusCode::PERMANENT_REDIRECT => (),
+                _ => break resp,
+            }
+            if redirects == self.max_redirects {
+                return Err(APIError::new(
+                    src,
+                    ErrorContext::Downloading,
+                    format!("gave up after {} redirects", redirects),
+                ));
+            }
+            redirects += 1;
+            target = Client::redirect(&target, &resp)?;
+        };
+        if !resp.status().is_success() {
+            return Err(APIError::new(
+                &target,
+                ErrorContext::Downloading,
+                resp.status(),
+            ));
+        }
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = hyper::body::to_bytes(resp)
+            .await
+            .map_err(|err| APIError::new(&target, ErrorContext::Downloading, err))?;
+        Ok(Response { content_type, body })
+    }
+
+    /// Works out where a redirect points to. The Location may well be relative to the URL
+    /// that was just requested.
+    fn redirect(from: &str, resp: &hyper::Response<hyper::Body>) -> APIResult<String> {
+        let location = resp
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                APIError::new(
+                    from,
+                    ErrorContext::Downloading,
+                    format!("{} without a Location", resp.status()),
+                )
+            })?;
+        url::Url::parse(from)
+            .and_then(|base| base.join(location))
+            .map(String::from)
+            .map_err(|err| APIError::new(location, ErrorContext::URIParsing, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves up a handful of routes on an arbitrary local port:
+    ///
+    /// /ok echoes back the User-Agent, /hop/N redirects (relatively) to /hop/N-1, bottoming out at /ok,
+    /// /loop redirects to itself, /hang never answers, and anything else is a 404.
+    fn serve() -> String {
+        let make_service = hyper::service::make_service_fn(|_| async {
+            Ok::<_, hyper::Error>(hyper::service::service_fn(
+                |req: hyper::Request<hyper::Body>| async move {
+                    let path = req.uri().path().to_string();
+                    let mut resp = hyper::Response::new(hyper::Body::empty());
+                    if path == "/ok" {
+                        let agent = req.headers().get(USER_AGENT).cloned();
+                        *resp.body_mut() = hyper::Body::from(
+                            agent
+                                .map(|agent| agent.as_bytes().to_vec())
+                                .unwrap_or_default(),
+                        );
+                        resp.headers_mut()
+                            .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+                    } else if let Some(hops) = path.strip_prefix("/hop/") {
+                        let hops: u32 = hops.parse().unwrap();
+                        let next = match hops {
+                            0 => "/ok".to_string(),
+                            hops => format!("{}", hops - 1),
+                        };
+                        *resp.status_mut() = StatusCode::FOUND;
+                        resp.headers_mut()
+                            .insert(LOCATION, HeaderValue::from_str(&next).unwrap());
+                    } else if path == "/hang" {
+                        tokio::time::delay_for(Duration::from_secs(3600)).await;
+                    } else if path == "/loop" {
+                        *resp.status_mut() = StatusCode::TEMPORARY_REDIRECT;
+                        resp.headers_mut()
+                            .insert(LOCATION, HeaderValue::from_static("/loop"));
+                    } else {
+                        *resp.status_mut() = StatusCode::NOT_FOUND;
+                    }
+                    Ok::<_, hyper::Error>(resp)
+                },
+            ))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        addr
+    }
+
+    #[test]
+    fn sends_the_user_agent() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let addr = serve();
+            let client = Client::new(&Settings {
+                user_agent: "test-agent/1.0".to_string(),
+                ..Settings::default()
+            });
+            let resp = client.get(format!("{}/ok", addr)).await.unwrap();
+            assert_eq!(&resp.body[..], b"test-agent/1.0");
+            assert_eq!(resp.content_type.as_deref(), Some("text/plain"));
+        });
+    }
+
+    #[test]
+    fn follows_redirects() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let addr = serve();
+            let client = Client::new(&Settings {
+                max_redirects: 3,
+                ..Settings::default()
+            });
+            // /hop/2 -> /hop/1 -> /hop/0 -> /ok
+            assert!(client.get(format!("{}/hop/2", addr)).await.is_ok());
+            assert!(client.get(format!("{}/hop/3", addr)).await.is_err());
+            let err = client.get(format!("{}/loop", addr)).await.err().unwrap();
+            assert!(err.to_string().contains("gave up after 3 redirects"));
+        });
+    }
+
+    #[test]
+    fn rejects_error_statuses() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let addr = serve();
+            let err = Client::default()
+                .get(format!("{}/missing", addr))
+                .await
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("404"));
+        });
+    }
+
+    #[test]
+    fn times_out() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let addr = serve();
+            let client = Client::new(&Settings {
+                request_timeout: Duration::from_millis(100),
+                ..Settings::default()
+            });
+            let err = client.get(format!("{}/hang", addr)).await.err().unwrap();
+            assert!(err.to_string().starts_with("Timed out"));
+        });
+    }
+}
@@ -0,0 +1,374 @@
+use crate::api;
+use crate::lineup::{Decisions, Game, Scoreboard, Sources};
+use crate::task::{self, Task};
+use std::time::Duration;
+
+/// How long the feed and linescore of a game have to arrive in.
+static FEED_DEADLINE: Duration = Duration::from_secs(45);
+
+/// Everything there is to say about a single game, for when the user wants more than a
+/// headline.
+///
+/// What the schedule already had to say about the game (the recap, the score) is shown right
+/// away, while the rest of it comes in from the game's linescore and live feed. The linescore
+/// endpoint is tiny compared to the feed, so the inning-by-inning grid usually shows up well
+/// before the box score does.
+pub struct Detail {
+    headline: String,
+    scoreboard: String,
+    venue: Option<String>,
+    away: String,
+    home: String,
+    decisions: Decisions,
+    recap: String,
+    linescore: Fetched<api::Linescore>,
+    feed: Fetched<api::Feed>,
+}
+
+/// Something that the detail view is waiting on from the API.
+enum Fetched<T> {
+    Loading(Task<api::APIResult<T>>),
+    Loaded(Box<T>),
+    Failed(api::APIError),
+}
+
+impl<T: Send + 'static> Fetched<T> {
+    fn spawn(src: String, fetch: api::Fetch<T>) -> Fetched<T> {
+        Fetched::Loading(Task::spawn(async move {
+            task::deadline(&src, FEED_DEADLINE, fetch).await
+        }))
+    }
+
+    fn poll(&mut self) {
+        if let Fetched::Loading(fetch) = self {
+            match fetch.poll() {
+                Some(Ok(loaded)) => *self = Fetched::Loaded(Box::new(loaded)),
+                Some(Err(err)) => *self = Fetched::Failed(err),
+                None => (),
+            }
+        }
+    }
+
+    fn loaded(&self) -> Option<&T> {
+        match self {
+            Fetched::Loaded(loaded) => Some(loaded),
+            _ => None,
+        }
+    }
+}
+
+/// The inning-by-inning runs of a game followed by its R/H/E totals, as rows of cells.
+///
+/// The first row holds the column headings and the other two hold the away and home teams, each
+/// led by the team's abbreviation. Innings that haven't come around yet are left blank so the
+/// grid keeps the same shape from the first pitch to the last.
+#[derive(Debug, PartialEq)]
+pub struct Grid {
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Grid {
+    pub fn new(linescore: &api::Linescore, away: &str, home: &str) -> Grid {
+        let scheduled = linescore.scheduled_innings.unwrap_or(9) as usize;
+        let columns = scheduled.max(linescore.innings.len());
+        let mut header = vec![String::new()];
+        header.extend((1..=columns).map(|num| num.to_string()));
+        header.extend(vec!["R".to_string(), "H".to_string(), "E".to_string()]);
+        let row = |team: &str, side: fn(&api::Inning) -> &api::Line, total: Option<&api::Line>| {
+            let mut row = vec![team.to_string()];
+            let mut innings = linescore.innings.iter().peekable();
+            for column in 1..=columns {
+                let inning = match innings.peek() {
+                    Some(inning) if inning.num as usize == column => innings.next(),
+                    _ => None,
+                };
+                row.push(match inning.map(|inning| side(inning).runs) {
+                    Some(Some(runs)) => runs.to_string(),
+                    // A half inning that went unplayed at the end of the game, such as the
+                    // bottom of the ninth when the home team is already ahead.
+                    Some(None) if column >= scheduled => "x".to_string(),
+                    _ => String::new(),
+                });
+            }
+            // The totals ought to always be there, but they're easy enough to work out if not.
+            let total = total.cloned().unwrap_or_else(|| {
+                linescore
+                    .innings
+                    .iter()
+                    .map(side)
+                    .fold(api::Line::default(), |total, line| api::Line {
+                        runs: Some(total.runs.unwrap_or(0) + line.runs.unwrap_or(0)),
+                        hits: total.hits + line.hits,
+                        errors: total.errors + line.errors,
+                    })
+            });
+            row.push(total.runs.unwrap_or(0).to_string());
+            row.push(total.hits.to_string());
+            row.push(total.errors.to_string());
+            row
+        };
+        let totals = linescore.teams.as_ref();
+        Grid {
+            rows: vec![
+                header,
+                row(away, |inning| &inning.away, totals.map(|t| &t.away)),
+                row(home, |inning| &inning.home, totals.map(|t| &t.home)),
+            ],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+}
+
+impl Detail {
+    pub fn new(sources: &Sources, game: &Game) -> Detail {
+        Detail {
+            headline: game.headline.clone(),
+            scoreboard: Scoreboard {
+                away: &game.away,
+                home: &game.home,
+                state: &game.state,
+            }
+            .to_string(),
+            venue: game.venue.clone(),
+            away: game.away.abbreviation.clone(),
+            home: game.home.abbreviation.clone(),
+            decisions: game.decisions.clone(),
+            recap: plain(&game.body),
+            linescore: Fetched::spawn(
+                format!("linescore of game {}", game.id),
+                sources.schedule.linescore(game.id),
+            ),
+            feed: Fetched::spawn(
+                format!("feed of game {}", game.id),
+                sources.schedule.feed(game.id),
+            ),
+        }
+    }
+
+    /// Picks up whatever has arrived since the last frame.
+    pub fn poll(&mut self) {
+        self.linescore.poll();
+        self.feed.poll();
+    }
+
+    /// The paragraphs that go above the grid. The venue gets a line of its own under the
+    /// score, when the schedule knows where the game is.
+    pub fn heading(&self) -> Vec<String> {
+        let mut heading = vec![self.headline.clone(), self.scoreboard.clone()];
+        heading.extend(self.venue.clone());
+        heading
+    }
+
+    /// The linescore of the game, once there is one. The feed has a linescore of its own, which
+    /// is used if the linescore endpoint didn't come through for whatever reason.
+    pub fn grid(&self) -> Option<Grid> {
+        let linescore = self.linescore.loaded().or_else(|| {
+            self.feed
+                .loaded()
+                .and_then(|feed| feed.live_data.linescore.as_ref())
+        })?;
+        Some(Grid::new(linescore, &self.away, &self.home))
+    }
+
+    /// The paragraphs that go below the grid, from top to bottom.
+    pub fn paragraphs(&self) -> Vec<String> {
+        let mut paragraphs = vec![];
+        match &self.feed {
+            Fetched::Loading(_) => paragraphs.push("Loading the box score\u{2026}".to_string()),
+            Fetched::Failed(err) => paragraphs.push(format!("No box score is available. {}", err)),
+            Fetched::Loaded(feed) => {
+                if let Some(boxscore) = &feed.live_data.boxscore {
+                    paragraphs.push(self.highlights(boxscore).join("\n"));
+                }
+            }
+        }
+        // The feed is more up to date than the schedule, if it has anything to say.
+        let decisions = self
+            .feed
+            .loaded()
+            .and_then(|feed| feed.live_data.decisions.as_ref())
+            .map(Decisions::from)
+            .unwrap_or_else(|| self.decisions.clone());
+        if decisions != Decisions::default() {
+            paragraphs.push(decisions.to_string());
+        }
+        paragraphs.push(match self.recap.as_str() {
+            "" => "No recap has been published for this game yet.".to_string(),
+            recap => recap.to_string(),
+        });
+        paragraphs
+    }
+
+    /// How each team did at the plate, followed by the odds and ends of the box score.
+    fn highlights(&self, boxscore: &api::Boxscore) -> Vec<String> {
+        let batting = |team: &str, batting: &api::Batting| {
+            format!(
+                "{}: {} H, {} HR, {} BB, {} K, {} LOB",
+                team,
+                batting.hits,
+                batting.home_runs,
+                batting.base_on_balls,
+                batting.strike_outs,
+                batting.left_on_base
+            )
+        };
+        let mut highlights = vec![
+            batting(&self.away, &boxscore.teams.away.team_stats.batting),
+            batting(&self.home, &boxscore.teams.home.team_stats.batting),
+        ];
+        highlights.extend(
+            boxscore
+                .info
+                .iter()
+                .filter_map(|info| Some(format!("{}: {}", info.label, info.value.as_ref()?))),
+        );
+        highlights
+    }
+}
+
+/// Recaps come as HTML, which is boiled down here to paragraphs of plain text.
+fn plain(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let tag = rest[start + 1..end].trim().to_ascii_lowercase();
+        if tag == "/p" || tag.starts_with("br") {
+            text.push('\n');
+        }
+        rest = &rest[end + 1..];
+    }
+    if !rest.contains('<') {
+        text.push_str(rest);
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lineup::{PhotoSource, Schedule};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            plain(
+                "<p>Machado homered.</p>\n<p>The O&#39;s won &amp; <b>Schoop</b> hit<br/>too</p>"
+            ),
+            "Machado homered.\nThe O's won & Schoop hit\ntoo"
+        );
+        assert_eq!(plain("no markup"), "no markup");
+        assert_eq!(plain("dangling <p"), "dangling");
+    }
+
+    #[test]
+    fn fills_in_from_the_feed() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let source = api::testing::FixtureSource::new(api::testing::TEST_DATA)
+                .with_feed(530781, api::testing::FEED_DATA)
+                .with_linescore(530781, api::testing::LINESCORE_DATA);
+            let sources = Sources {
+                schedule: Arc::new(source),
+                photos: PhotoSource::Directory(PathBuf::from("assets")),
+            };
+            let query = api::ScheduleQuery::new(chrono::NaiveDate::from_ymd_opt(2018, 6, 10).unwrap());
+            let schedule = Schedule::new(sources.schedule.fetch(&query).await.unwrap(), &sources.photos);
+            let mut detail = Detail::new(&sources, schedule.focused().unwrap());
+            loop {
+                detail.poll();
+                match (&detail.linescore, &detail.feed) {
+                    (Fetched::Loading(_), _) | (_, Fetched::Loading(_)) => {
+                        tokio::time::delay_for(Duration::from_millis(10)).await
+                    }
+                    _ => break,
+                }
+            }
+            assert_eq!(
+                detail.heading(),
+                vec![
+                    "Machado, Schoop lift O's past Rays",
+                    "TB 3  @  BAL 4   Final",
+                    "Oriole Park at Camden Yards",
+                ]
+            );
+            let grid = detail.grid().unwrap();
+            assert_eq!(
+                grid.rows,
+                vec![
+                    vec!["", "1", "2", "3", "4", "5", "6", "7", "8", "9", "R", "H", "E"],
+                    vec!["TB", "0", "1", "0", "0", "2", "0", "0", "0", "0", "3", "7", "1"],
+                    vec!["BAL", "1", "0", "0", "0", "0", "2", "0", "1", "x", "4", "9", "0"],
+                ]
+            );
+            assert_eq!(grid.columns(), 13);
+            assert_eq!(
+                detail.paragraphs(),
+                vec![
+                    "TB: 7 H, 1 HR, 2 BB, 9 K, 5 LOB\nBAL: 9 H, 2 HR, 3 BB, 6 K, 5 LOB\nWP: Gausman.\n\
+                     HBP: Duffy (by Gausman).\n\
+                     Pitches-strikes: Snell 98-62, Romo 14-10, Gausman 104-70, Britton 12-8.\n\
+                     Weather: 78 degrees, Partly Cloudy.\nT: 2:54.\nAtt: 24,415.",
+                    "W: Kevin Gausman   L: Sergio Romo   SV: Zach Britton",
+                    "Orioles win third straight after Machado's go-ahead homer in the eighth",
+                ]
+            );
+            // Without a feed or a linescore, there's still the schedule to go on.
+            let mut detail = Detail::new(&sources, &schedule.games[6]);
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+            detail.poll();
+            assert!(detail.grid().is_none());
+            let paragraphs = detail.paragraphs();
+            assert!(paragraphs[0].starts_with("No box score is available."));
+            assert_eq!(
+                paragraphs.last().unwrap(),
+                "No recap has been published for this game yet."
+            );
+        });
+    }
+
+    #[test]
+    fn grid_of_a_game_in_progress() {
+        let linescore: api::Linescore = serde_json::from_str(
+            r#"{
+                "currentInning": 3,
+                "inningState": "Top",
+                "innings": [
+                    {"num": 1, "ordinalNum": "1st", "away": {"runs": 2, "hits": 3}, "home": {"runs": 0}},
+                    {"num": 2, "ordinalNum": "2nd", "away": {"runs": 0}, "home": {"runs": 1, "hits": 2, "errors": 1}},
+                    {"num": 3, "ordinalNum": "3rd", "away": {"runs": 0, "hits": 1}, "home": {}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let grid = Grid::new(&linescore, "NYM", "NYY");
+        assert_eq!(grid.columns(), 13);
+        // The bottom of the third is still to come, and the totals have to be added up.
+        assert_eq!(
+            grid.rows[1..],
+            [
+                vec!["NYM", "2", "0", "0", "", "", "", "", "", "", "2", "4", "0"],
+                vec!["NYY", "0", "1", "", "", "", "", "", "", "", "1", "2", "1"],
+            ]
+        );
+    }
+}
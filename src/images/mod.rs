@@ -0,0 +1,115 @@
+use image::{ImageError, ImageFormat, RgbaImage};
+use std::fmt::Formatter;
+
+/// Decodes an image of whatever format it happens to be in.
+///
+/// The format is sniffed from the magic bytes at the start of the buffer, as those are far
+/// more trustworthy than whatever the server claims. The Content-Type is only consulted if
+/// the bytes themselves are not recognized.
+pub fn decode(content_type: Option<&str>, buf: &[u8]) -> Result<RgbaImage, DecodeError> {
+    let format = sniff(content_type, buf)?;
+    image::load_from_memory_with_format(buf, format)
+        .map(|image| image.into_rgba())
+        .map_err(|err| match err {
+            ImageError::UnsupportedError(_) => DecodeError::Unsupported(format!("{:?}", format)),
+            err => DecodeError::Malformed(format, err.to_string()),
+        })
+}
+
+pub fn sniff(content_type: Option<&str>, buf: &[u8]) -> Result<ImageFormat, DecodeError> {
+    if let Ok(format) = image::guess_format(buf) {
+        return Ok(format);
+    }
+    match content_type {
+        Some(content_type) => from_content_type(content_type)
+            .ok_or_else(|| DecodeError::Unsupported(content_type.to_string())),
+        None => Err(DecodeError::Unsupported("unrecognized data".to_string())),
+    }
+}
+
+fn from_content_type(content_type: &str) -> Option<ImageFormat> {
+    // Strip off any parameters, E.G. "image/jpeg; charset=binary"
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some(ImageFormat::JPEG),
+        "image/png" => Some(ImageFormat::PNG),
+        "image/gif" => Some(ImageFormat::GIF),
+        "image/webp" => Some(ImageFormat::WEBP),
+        "image/bmp" | "image/x-ms-bmp" => Some(ImageFormat::BMP),
+        "image/tiff" => Some(ImageFormat::TIFF),
+        "image/x-icon" | "image/vnd.microsoft.icon" => Some(ImageFormat::ICO),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    /// The data is in a format that we either don't recognize or can't decode.
+    Unsupported(String),
+    /// The data claimed to be in the given format, but didn't decode as one.
+    Malformed(ImageFormat, String),
+}
+
+impl std::error::Error for DecodeError {}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Unsupported(format) => {
+                f.write_fmt(format_args!("Unsupported image format ({})", format))
+            }
+            Self::Malformed(format, err) => {
+                f.write_fmt(format_args!("Malformed {:?} image ({})", format, err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static JPEG: &[u8] = include_bytes!("../../assets/mlb_logo_small.jpg");
+    static PNG: &[u8] = include_bytes!("../../assets/left_arrow.png");
+
+    #[test]
+    fn magic_bytes_win() {
+        assert_eq!(sniff(None, JPEG), Ok(ImageFormat::JPEG));
+        assert_eq!(sniff(Some("image/png"), JPEG), Ok(ImageFormat::JPEG));
+        assert_eq!(sniff(Some("image/jpeg"), PNG), Ok(ImageFormat::PNG));
+    }
+
+    #[test]
+    fn falls_back_to_content_type() {
+        assert_eq!(
+            sniff(Some("image/webp; charset=binary"), b"????"),
+            Ok(ImageFormat::WEBP)
+        );
+        assert_eq!(sniff(Some("IMAGE/JPG"), b"????"), Ok(ImageFormat::JPEG));
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            sniff(Some("text/html"), b"<html>"),
+            Err(DecodeError::Unsupported("text/html".to_string()))
+        );
+        assert!(sniff(None, b"<html>").is_err());
+    }
+
+    #[test]
+    fn decodes_assets() {
+        let logo = decode(Some("image/jpeg"), JPEG).unwrap();
+        assert_eq!(logo.dimensions(), (320, 180));
+        let arrow = decode(None, PNG).unwrap();
+        assert_eq!(arrow.dimensions(), (256, 256));
+    }
+
+    #[test]
+    fn malformed() {
+        match decode(None, &JPEG[..64]) {
+            Err(DecodeError::Malformed(ImageFormat::JPEG, _)) => (),
+            other => panic!("expected a malformed JPEG, got {:?}", other.map(|_| ())),
+        }
+    }
+}
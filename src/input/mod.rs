@@ -0,0 +1,521 @@
+use piston_window::{Button, ButtonArgs, ButtonState, GenericEvent, Key};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The things the user can ask of the application, whatever it is they're holding in their hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Right,
+    /// Opens up the focused game (or retries a schedule that failed to load).
+    Select,
+    /// Closes the detail view or, from the lineup, quits. Remotes tend to have a back button and
+    /// not much else, so it has to do both.
+    Back,
+    NextDay,
+    PrevDay,
+    /// Retries a schedule that failed to load.
+    Retry,
+}
+
+impl std::str::FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Action, String> {
+        match s {
+            "Left" => Ok(Action::Left),
+            "Right" => Ok(Action::Right),
+            "Select" => Ok(Action::Select),
+            "Back" => Ok(Action::Back),
+            "NextDay" => Ok(Action::NextDay),
+            "PrevDay" => Ok(Action::PrevDay),
+            "Retry" => Ok(Action::Retry),
+            _ => Err(format!(
+                "{:?} is not one of Left, Right, Select, Back, NextDay, PrevDay or Retry",
+                s
+            )),
+        }
+    }
+}
+
+/// Something on a keyboard, mouse or gamepad that can be bound to an action.
+///
+/// Gamepad buttons go by their number alone, whichever gamepad they're on, so that a second
+/// gamepad (or one that was unplugged and plugged back in) just works. They're numbered the way
+/// SDL numbers them for an Xbox style pad: A, B, X and Y are 0 through 3, Back, Guide and Start
+/// are 4 through 6, the sticks are 7 and 8, the shoulders are 9 and 10 and the D-pad is 11
+/// (up) through 14 (right).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Control {
+    Key(Key),
+    Button(u8),
+    WheelUp,
+    WheelDown,
+    WheelLeft,
+    WheelRight,
+}
+
+/// Controls are written out as "key:<piston key>", "button:<number>" or
+/// "wheel:<up|down|left|right>", such as "key:PageDown", "button:0" or "wheel:up".
+impl std::str::FromStr for Control {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Control, String> {
+        let invalid = |err: &dyn std::fmt::Display| format!("{:?} is not a control ({})", s, err);
+        let (kind, name) = match s.find(':') {
+            Some(at) => (&s[..at], &s[at + 1..]),
+            None => return Err(invalid(&"expected a kind and a name, such as key:Left")),
+        };
+        // Piston's own names for keys are what serde knows them by.
+        match kind {
+            "key" => Key::deserialize(name.into_deserializer())
+                .map(Control::Key)
+                .map_err(|err: serde::de::value::Error| invalid(&err)),
+            "button" => name
+                .parse()
+                .map(Control::Button)
+                .map_err(|err| invalid(&err)),
+            "wheel" => match name {
+                "up" => Ok(Control::WheelUp),
+                "down" => Ok(Control::WheelDown),
+                "left" => Ok(Control::WheelLeft),
+                "right" => Ok(Control::WheelRight),
+                _ => Err(invalid(&"the wheel goes up, down, left or right")),
+            },
+            _ => Err(invalid(&"the kinds are key, button and wheel")),
+        }
+    }
+}
+
+/// Which controls do what.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    /// In the order they were listed in, which is the order that the on-screen hints go by.
+    actions: Vec<(Control, Action)>,
+}
+
+impl Default for Bindings {
+    /// The keyboard works the way it always has. On a gamepad the D-pad moves around, A opens
+    /// a game up, B backs out, Y retries and the shoulder buttons flip through the days.
+    fn default() -> Bindings {
+        use Control::{Button, WheelDown, WheelLeft, WheelRight, WheelUp};
+        let defaults = vec![
+            (
+                Action::Left,
+                vec![Control::Key(Key::Left), Button(13), WheelUp, WheelLeft],
+            ),
+            (
+                Action::Right,
+                vec![Control::Key(Key::Right), Button(14), WheelDown, WheelRight],
+            ),
+            (Action::Select, vec![Control::Key(Key::Return), Button(0)]),
+            (
+                Action::Back,
+                vec![
+                    Control::Key(Key::Escape),
+                    Control::Key(Key::Backspace),
+                    Button(1),
+                ],
+            ),
+            (
+                Action::NextDay,
+                vec![
+                    Control::Key(Key::Down),
+                    Control::Key(Key::PageDown),
+                    Button(10),
+                    Button(12),
+                ],
+            ),
+            (
+                Action::PrevDay,
+                vec![
+                    Control::Key(Key::Up),
+                    Control::Key(Key::PageUp),
+                    Button(9),
+                    Button(11),
+                ],
+            ),
+            (Action::Retry, vec![Control::Key(Key::R), Button(3)]),
+        ];
+        let actions = defaults
+            .into_iter()
+            .flat_map(|(action, controls)| {
+                controls.into_iter().map(move |control| (control, action))
+            })
+            .collect();
+        Bindings { actions }
+    }
+}
+
+impl Bindings {
+    /// Reads bindings out of a TOML file. See from_toml. Whatever goes wrong is described in
+    /// terms of the file, as that's the thing the user has to go and fix.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Bindings, String> {
+        let path = path.as_ref().display();
+        let text = std::fs::read_to_string(path.to_string())
+            .map_err(|err| format!("Failed to read the bindings file {} ({})", path, err))?;
+        Bindings::from_toml(&text)
+            .map_err(|err| format!("The bindings file {} is invalid: {}", path, err))
+    }
+
+    /// Bindings from a TOML table of actions, each with the list of controls that trigger it:
+    ///
+    /// ```toml
+    /// Left = ["key:Left", "key:A", "button:13"]
+    /// Select = ["key:Return", "key:Space", "button:0"]
+    /// ```
+    ///
+    /// Every action that is listed has its default controls replaced, and every one that isn't
+    /// keeps them (apart from any that the file gives to something else). A control can only be
+    /// listed under the one action.
+    pub fn from_toml(text: &str) -> Result<Bindings, String> {
+        let table: toml::value::Table = toml::from_str(text).map_err(|err| err.to_string())?;
+        let mut listed: Vec<(Control, Action)> = vec![];
+        for (name, controls) in table {
+            let action: Action = name.parse()?;
+            let controls: Vec<String> = controls
+                .try_into()
+                .map_err(|err| format!("{} is not a list of controls ({})", name, err))?;
+            for control in controls {
+                let control: Control = control.parse()?;
+                match listed.iter().find(|(listed, _)| *listed == control) {
+                    Some(&(_, other)) if other != action => {
+                        return Err(format!(
+                            "{:?} is bound to both {:?} and {:?}",
+                            control, other, action
+                        ))
+                    }
+                    Some(_) => (),
+                    None => listed.push((control, action)),
+                }
+            }
+        }
+        let mut bindings = Bindings::default();
+        bindings.actions.retain(|&(control, bound)| {
+            !listed
+                .iter()
+                .any(|&(listed, action)| action == bound || listed == control)
+        });
+        bindings.actions.extend(listed);
+        Ok(bindings)
+    }
+
+    pub fn action(&self, control: Control) -> Option<Action> {
+        self.actions
+            .iter()
+            .find(|(bound, _)| *bound == control)
+            .map(|&(_, action)| action)
+    }
+
+    /// The name of the first key bound to the given action, for telling the user what to press.
+    pub fn key_name(&self, action: Action) -> Option<String> {
+        let key = self
+            .actions
+            .iter()
+            .find_map(|&(control, bound)| match control {
+                Control::Key(key) if bound == action => Some(key),
+                _ => None,
+            })?;
+        Some(match key {
+            // That's what it says on the key.
+            Key::Return => "Enter".to_string(),
+            key => format!("{:?}", key),
+        })
+    }
+}
+
+/// What happened to an action, as far as the event loop cares.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Signal {
+    /// Whatever triggers the action went down, and is being held there.
+    Press(Action),
+    /// And then came back up again.
+    Release(Action),
+    /// The action happened all at once, with nothing to hold (a click of the mouse wheel).
+    Tap(Action),
+}
+
+/// Turns raw input into signals, according to the bindings.
+pub struct Controls {
+    bindings: Bindings,
+    /// None when the gamepads couldn't be opened, or when built without them.
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+}
+
+impl Controls {
+    pub fn new(bindings: Bindings) -> Controls {
+        Controls {
+            bindings,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(),
+        }
+    }
+
+    /// The signals from a window event, along with any from the gamepads since last time.
+    pub fn signals<E: GenericEvent>(&mut self, e: &E) -> Vec<Signal> {
+        let mut signals = vec![];
+        if let Some(ButtonArgs {
+            state,
+            button: Button::Keyboard(key),
+            ..
+        }) = e.button_args()
+        {
+            signals.extend(self.button(Control::Key(key), state));
+        }
+        if let Some([x, y]) = e.mouse_scroll_args() {
+            let controls = [
+                (y > 0.0, Control::WheelUp),
+                (y < 0.0, Control::WheelDown),
+                (x < 0.0, Control::WheelLeft),
+                (x > 0.0, Control::WheelRight),
+            ];
+            for &(scrolled, control) in controls.iter() {
+                if let Some(action) = self.bindings.action(control).filter(|_| scrolled) {
+                    signals.push(Signal::Tap(action));
+                }
+            }
+        }
+        #[cfg(feature = "gamepad")]
+        for (number, state) in self
+            .gamepads
+            .as_mut()
+            .map(Gamepads::poll)
+            .unwrap_or_default()
+        {
+            signals.extend(self.button(Control::Button(number), state));
+        }
+        signals
+    }
+
+    fn button(&self, control: Control, state: ButtonState) -> Option<Signal> {
+        let action = self.bindings.action(control)?;
+        Some(match state {
+            ButtonState::Press => Signal::Press(action),
+            ButtonState::Release => Signal::Release(action),
+        })
+    }
+}
+
+/// The gamepads plugged into the machine. piston's window only ever tells us about the
+/// keyboard and mouse, so gamepads are read on the side with gilrs.
+#[cfg(feature = "gamepad")]
+struct Gamepads {
+    gilrs: gilrs::Gilrs,
+    /// The buttons being held down on each gamepad, so that they can be let go of should the
+    /// gamepad get unplugged with one of them down (which would otherwise repeat forever).
+    held: Vec<(gilrs::GamepadId, u8)>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    fn new() -> Option<Gamepads> {
+        match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(Gamepads {
+                gilrs,
+                held: vec![],
+            }),
+            Err(err) => {
+                eprintln!("Gamepads are unavailable ({})", err);
+                None
+            }
+        }
+    }
+
+    /// Every button that has gone down or come up since last time, by its number.
+    fn poll(&mut self) -> Vec<(u8, ButtonState)> {
+        let mut buttons = vec![];
+        while let Some(gilrs::Event { id, event, .. }) = self.gilrs.next_event() {
+            match event {
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    if let Some(number) = button_number(button) {
+                        if !self.held.contains(&(id, number)) {
+                            self.held.push((id, number));
+                            buttons.push((number, ButtonState::Press));
+                        }
+                    }
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    if let Some(number) = button_number(button) {
+                        self.held.retain(|&held| held != (id, number));
+                        buttons.push((number, ButtonState::Release));
+                    }
+                }
+                gilrs::EventType::Disconnected => {
+                    for &(_, number) in self.held.iter().filter(|held| held.0 == id) {
+                        buttons.push((number, ButtonState::Release));
+                    }
+                    self.held.retain(|held| held.0 != id);
+                }
+                _ => (),
+            }
+        }
+        buttons
+    }
+}
+
+/// The SDL number (see Control) of each of the buttons gilrs knows about. The triggers are
+/// left out, as SDL treats them as axes rather than buttons.
+#[cfg(feature = "gamepad")]
+fn button_number(button: gilrs::Button) -> Option<u8> {
+    use gilrs::Button::*;
+    match button {
+        South => Some(0),
+        East => Some(1),
+        West => Some(2),
+        North => Some(3),
+        Select => Some(4),
+        Mode => Some(5),
+        Start => Some(6),
+        LeftThumb => Some(7),
+        RightThumb => Some(8),
+        LeftTrigger => Some(9),
+        RightTrigger => Some(10),
+        DPadUp => Some(11),
+        DPadDown => Some(12),
+        DPadLeft => Some(13),
+        DPadRight => Some(14),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston_window::{Event, Input, Motion};
+
+    fn button(state: ButtonState, button: Button) -> Event {
+        Event::Input(
+            Input::Button(ButtonArgs {
+                state,
+                button,
+                scancode: None,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn parses_controls() {
+        assert_eq!("key:PageDown".parse(), Ok(Control::Key(Key::PageDown)));
+        assert_eq!("button:10".parse(), Ok(Control::Button(10)));
+        assert_eq!("wheel:down".parse(), Ok(Control::WheelDown));
+        for bad in &["Left", "key:Sideways", "button:A", "hat:Left", "joystick:0"] {
+            assert!(bad.parse::<Control>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn remaps_from_toml() {
+        let bindings = Bindings::from_toml(
+            r#"
+            Select = ["key:Space", "button:7"]
+            Back = ["key:Escape", "key:Return"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            bindings.action(Control::Key(Key::Space)),
+            Some(Action::Select)
+        );
+        assert_eq!(bindings.action(Control::Button(7)), Some(Action::Select));
+        // Listed actions lose their defaults, and controls go to whatever they're listed under
+        // (Return leaves Select behind for Back).
+        assert_eq!(bindings.action(Control::Button(0)), None);
+        assert_eq!(
+            bindings.action(Control::Key(Key::Return)),
+            Some(Action::Back)
+        );
+        assert_eq!(bindings.action(Control::Button(1)), None);
+        // Everything else is left alone.
+        assert_eq!(bindings.action(Control::Key(Key::Left)), Some(Action::Left));
+        assert_eq!(bindings.action(Control::Button(12)), Some(Action::NextDay));
+        assert_eq!(Bindings::from_toml(""), Ok(Bindings::default()));
+        // Taking a control away from an action that isn't listed takes it away for good, and
+        // leaves the rest of that action's controls be.
+        let stolen =
+            Bindings::from_toml("Back = [\"key:Return\"]\nSelect = [\"key:Left\"]").unwrap();
+        assert_eq!(stolen.action(Control::Key(Key::Return)), Some(Action::Back));
+        assert_eq!(stolen.action(Control::Key(Key::Left)), Some(Action::Select));
+        assert_eq!(stolen.action(Control::Button(13)), Some(Action::Left));
+        let stolen = Bindings::from_toml("Back = [\"key:Return\"]").unwrap();
+        assert_eq!(stolen.action(Control::Key(Key::Return)), Some(Action::Back));
+        assert_eq!(stolen.action(Control::Button(0)), Some(Action::Select));
+        assert_eq!(stolen.key_name(Action::Select), None);
+        // Hints name the first key listed, if there is one.
+        assert_eq!(bindings.key_name(Action::Back), Some("Escape".to_string()));
+        assert_eq!(bindings.key_name(Action::Select), Some("Space".to_string()));
+        assert_eq!(
+            Bindings::default().key_name(Action::Select),
+            Some("Enter".to_string())
+        );
+        let gamepad = Bindings::from_toml("NextDay = [\"button:10\"]").unwrap();
+        assert_eq!(gamepad.key_name(Action::NextDay), None);
+        for bad in &[
+            "Jump = [\"key:Space\"]",
+            "Select = \"key:Space\"",
+            "Select = [\"key:Sideways\"]",
+            "Select = [\"key:Space\"]\nBack = [\"key:Space\"]",
+            "Select = [",
+        ] {
+            assert!(Bindings::from_toml(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn names_the_bindings_file() {
+        let missing = Bindings::load("no/such/bindings.toml").unwrap_err();
+        assert!(
+            missing.starts_with("Failed to read the bindings file no/such/bindings.toml"),
+            "{}",
+            missing
+        );
+    }
+
+    #[test]
+    fn signals_from_events() {
+        let mut controls = Controls::new(Bindings::default());
+        let left = Button::Keyboard(Key::Left);
+        assert_eq!(
+            controls.signals(&button(ButtonState::Press, left)),
+            vec![Signal::Press(Action::Left)]
+        );
+        assert_eq!(
+            controls.signals(&button(ButtonState::Release, left)),
+            vec![Signal::Release(Action::Left)]
+        );
+        // Nothing is bound to the mouse buttons, or to Q.
+        let click = Button::Mouse(piston_window::MouseButton::Left);
+        assert!(controls
+            .signals(&button(ButtonState::Press, click))
+            .is_empty());
+        let q = Button::Keyboard(Key::Q);
+        assert!(controls.signals(&button(ButtonState::Press, q)).is_empty());
+        let scroll = |x, y| Event::Input(Input::Move(Motion::MouseScroll([x, y])), None);
+        assert_eq!(
+            controls.signals(&scroll(0.0, -1.0)),
+            vec![Signal::Tap(Action::Right)]
+        );
+        assert_eq!(
+            controls.signals(&scroll(-1.0, 1.0)),
+            vec![Signal::Tap(Action::Left), Signal::Tap(Action::Left)]
+        );
+    }
+
+    #[cfg(feature = "gamepad")]
+    #[test]
+    fn gamepad_buttons() {
+        let bindings = Bindings::default();
+        let action =
+            |button| button_number(button).and_then(|n| bindings.action(Control::Button(n)));
+        assert_eq!(action(gilrs::Button::South), Some(Action::Select));
+        assert_eq!(action(gilrs::Button::East), Some(Action::Back));
+        assert_eq!(action(gilrs::Button::North), Some(Action::Retry));
+        assert_eq!(action(gilrs::Button::DPadLeft), Some(Action::Left));
+        assert_eq!(action(gilrs::Button::DPadRight), Some(Action::Right));
+        assert_eq!(action(gilrs::Button::LeftTrigger), Some(Action::PrevDay));
+        assert_eq!(action(gilrs::Button::RightTrigger), Some(Action::NextDay));
+        assert_eq!(action(gilrs::Button::LeftTrigger2), None);
+    }
+}
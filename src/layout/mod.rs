@@ -0,0 +1,405 @@
+use graphics::types::{FontSize, Rectangle};
+
+/// The resolution that the application was originally designed against. Every measurement in
+/// here is given in terms of this resolution and then scaled to fit the actual window.
+static REFERENCE: [f64; 2] = [1920.0, 1080.0];
+
+static PADDING: f64 = 27.5;
+static FONT_SIZE: f64 = 16.0;
+static LARGE: [f64; 2] = [480.0, 270.0];
+static SMALL: [f64; 2] = [320.0, 180.0];
+static ARROW: [f64; 2] = [256.0, 256.0];
+/// Where the tops of the large and small tiles sit.
+static LARGE_TOP: f64 = 540.0;
+static SMALL_TOP: f64 = 578.5;
+/// Baselines of the focused tile's heading and subheading, relative to the tile itself. The
+/// subheading makes room for the scoreboard and decisions underneath the tile.
+static HEADING_OFFSET: [f64; 2] = [40.0, -40.0];
+static SUBHEADING_OFFSET: f64 = 45.0 + SCOREBOARD_HEIGHT + DECISIONS_HEIGHT;
+/// The line of pitching decisions underneath the focused tile's scoreboard.
+static DECISIONS_HEIGHT: f64 = 28.0;
+static DECISIONS_BASELINE: f64 = 22.0;
+/// The strip underneath every tile that holds its scoreboard, and where its text sits within it.
+static SCOREBOARD_HEIGHT: f64 = 32.0;
+static SCOREBOARD_TEXT: [f64; 2] = [8.0, 22.0];
+static CAPTION_INSET: f64 = 16.0;
+/// The baseline of the headers that say which day the games underneath them are from, and the
+/// width of the line that separates one day from the next.
+static DAY_TOP: f64 = 440.0;
+static SEPARATOR_WIDTH: f64 = 2.0;
+static NOTICE_TOP: f64 = 500.0;
+static DETAIL_TOP: f64 = 120.0;
+/// The grid in the detail view (the linescore), which has a wider first column for the team
+/// names. Text is inset from the left edge of its cell and sits on a baseline within its row.
+static GRID_LABEL: f64 = 72.0;
+static GRID_CELL: f64 = 40.0;
+static GRID_ROW: f64 = 28.0;
+static GRID_TEXT: [f64; 2] = [8.0, 20.0];
+
+/// The two sizes that a tile within the carousel can come in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tile {
+    Small,
+    Large,
+}
+
+/// The left edge and baseline of a column of text, and how wide it may grow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextArea {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+}
+
+/// Computes where everything goes on screen for a given window size.
+///
+/// The original 1920x1080 design is scaled uniformly so that it fits within the window and is
+/// then centered, leaving bars along whichever axis has room to spare. The background is the
+/// exception in that it always stretches to fill the entire window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    size: [f64; 2],
+    scale: f64,
+    origin: [f64; 2],
+}
+
+impl Layout {
+    pub fn new(size: [f64; 2]) -> Layout {
+        let scale = (size[0] / REFERENCE[0]).min(size[1] / REFERENCE[1]);
+        Layout {
+            size,
+            scale,
+            origin: [
+                (size[0] - REFERENCE[0] * scale) / 2.0,
+                (size[1] - REFERENCE[1] * scale) / 2.0,
+            ],
+        }
+    }
+
+    pub fn fullscreen(&self) -> Rectangle {
+        [0.0, 0.0, self.size[0], self.size[1]]
+    }
+
+    pub fn font_size(&self) -> FontSize {
+        ((FONT_SIZE * self.scale).round() as FontSize).max(1)
+    }
+
+    /// Lays out the tiles of a page from left to right, each separated by padding, and centers
+    /// them across the window. The design only has room for five tiles, so a page with more
+    /// than that gets shrunk down (gaps and all) until it fits, with at least half the padding
+    /// left over at either side.
+    pub fn tiles(&self, tiles: &[Tile]) -> Vec<Rectangle> {
+        let size = |tile: &Tile| match tile {
+            Tile::Large => (LARGE, LARGE_TOP),
+            Tile::Small => (SMALL, SMALL_TOP),
+        };
+        let width = tiles.iter().map(|tile| size(tile).0[0]).sum::<f64>()
+            + tiles.len().saturating_sub(1) as f64 * PADDING;
+        let fit = ((REFERENCE[0] - PADDING) / width).min(1.0);
+        let mut left = (REFERENCE[0] - width * fit) / 2.0;
+        tiles
+            .iter()
+            .map(|tile| {
+                let (size, top) = size(tile);
+                let rect = self.rect([left, top, size[0] * fit, size[1] * fit]);
+                left += (size[0] + PADDING) * fit;
+                rect
+            })
+            .collect()
+    }
+
+    /// The heading sits just above the given (large) tile.
+    pub fn heading(&self, tile: Rectangle) -> TextArea {
+        TextArea {
+            x: tile[0] + HEADING_OFFSET[0] * self.scale,
+            y: tile[1] + HEADING_OFFSET[1] * self.scale,
+            width: tile[2] - HEADING_OFFSET[0] * self.scale,
+        }
+    }
+
+    /// The subheading sits just below the given (large) tile.
+    pub fn subheading(&self, tile: Rectangle) -> TextArea {
+        TextArea {
+            x: tile[0],
+            y: tile[1] + tile[3] + SUBHEADING_OFFSET * self.scale,
+            width: tile[2],
+        }
+    }
+
+    /// The strip that runs along underneath a tile.
+    pub fn scoreboard(&self, tile: Rectangle) -> Rectangle {
+        [
+            tile[0],
+            tile[1] + tile[3],
+            tile[2],
+            SCOREBOARD_HEIGHT * self.scale,
+        ]
+    }
+
+    /// The text within a scoreboard strip.
+    pub fn scoreboard_text(&self, strip: Rectangle) -> TextArea {
+        TextArea {
+            x: strip[0] + SCOREBOARD_TEXT[0] * self.scale,
+            y: strip[1] + SCOREBOARD_TEXT[1] * self.scale,
+            width: strip[2] - 2.0 * SCOREBOARD_TEXT[0] * self.scale,
+        }
+    }
+
+    /// The line of decisions that sits underneath the (large) tile's scoreboard.
+    pub fn decisions(&self, tile: Rectangle) -> TextArea {
+        TextArea {
+            x: tile[0],
+            y: tile[1] + tile[3] + (SCOREBOARD_HEIGHT + DECISIONS_BASELINE) * self.scale,
+            width: tile[2],
+        }
+    }
+
+    /// The header above a tile that says which day it's from. It sits well above the heading of
+    /// the large tile so that the two don't run into each other.
+    pub fn day(&self, tile: Rectangle) -> TextArea {
+        TextArea {
+            x: tile[0],
+            y: self.point([0.0, DAY_TOP])[1],
+            width: tile[2],
+        }
+    }
+
+    /// The line that starts a new day, down the middle of the gap between a tile and the one
+    /// before it, running from the day's header down to the bottom of the large tile's
+    /// scoreboard.
+    pub fn separator(&self, previous: Rectangle, tile: Rectangle) -> Rectangle {
+        let top = DAY_TOP - FONT_SIZE;
+        let bottom = LARGE_TOP + LARGE[1] + SCOREBOARD_HEIGHT;
+        let middle = (previous[0] + previous[2] + tile[0]) / 2.0;
+        [
+            middle - SEPARATOR_WIDTH / 2.0 * self.scale,
+            self.point([0.0, top])[1],
+            SEPARATOR_WIDTH * self.scale,
+            (bottom - top) * self.scale,
+        ]
+    }
+
+    /// Text that is written over the top of a tile, inset from its edges.
+    pub fn caption(&self, tile: Rectangle) -> TextArea {
+        let inset = CAPTION_INSET * self.scale;
+        TextArea {
+            x: tile[0] + inset,
+            y: tile[1] + inset + FONT_SIZE * self.scale,
+            width: tile[2] - 2.0 * inset,
+        }
+    }
+
+    /// Where to write messages that stand in for the lineup, such as loading and error text.
+    pub fn notice(&self) -> TextArea {
+        let [x, y] = self.point([PADDING, NOTICE_TOP]);
+        TextArea {
+            x,
+            y,
+            width: (REFERENCE[0] - 2.0 * PADDING) * self.scale,
+        }
+    }
+
+    /// Where the paragraphs of the game detail view start.
+    pub fn detail(&self) -> TextArea {
+        let [x, y] = self.point([PADDING, DETAIL_TOP]);
+        TextArea {
+            x,
+            y,
+            width: (REFERENCE[0] - 2.0 * PADDING) * self.scale,
+        }
+    }
+
+    /// The backdrop of a grid whose top left corner sits at the given point, with a label column
+    /// followed by the rest of the given number of columns.
+    pub fn grid(&self, corner: [f64; 2], rows: usize, columns: usize) -> Rectangle {
+        let cells = columns.saturating_sub(1) as f64;
+        [
+            corner[0],
+            corner[1],
+            (GRID_LABEL + cells * GRID_CELL + GRID_TEXT[0]) * self.scale,
+            rows as f64 * GRID_ROW * self.scale,
+        ]
+    }
+
+    /// The left edge and baseline of the text in the given cell of a grid.
+    pub fn grid_cell(&self, grid: Rectangle, row: usize, column: usize) -> [f64; 2] {
+        let left = match column {
+            0 => 0.0,
+            column => GRID_LABEL + (column - 1) as f64 * GRID_CELL,
+        };
+        [
+            grid[0] + (left + GRID_TEXT[0]) * self.scale,
+            grid[1] + (row as f64 * GRID_ROW + GRID_TEXT[1]) * self.scale,
+        ]
+    }
+
+    /// The scroll indicators are pinned to the top corners of the window itself.
+    pub fn left_arrow(&self) -> Rectangle {
+        [0.0, 0.0, ARROW[0] * self.scale, ARROW[1] * self.scale]
+    }
+
+    pub fn right_arrow(&self) -> Rectangle {
+        let width = ARROW[0] * self.scale;
+        [self.size[0] - width, 0.0, width, ARROW[1] * self.scale]
+    }
+
+    fn point(&self, point: [f64; 2]) -> [f64; 2] {
+        [
+            self.origin[0] + point[0] * self.scale,
+            self.origin[1] + point[1] * self.scale,
+        ]
+    }
+
+    fn rect(&self, rect: Rectangle) -> Rectangle {
+        let [x, y] = self.point([rect[0], rect[1]]);
+        [x, y, rect[2] * self.scale, rect[3] * self.scale]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PAGE: [Tile; 5] = [
+        Tile::Small,
+        Tile::Small,
+        Tile::Large,
+        Tile::Small,
+        Tile::Small,
+    ];
+
+    #[test]
+    fn reference_resolution_is_unscaled() {
+        let layout = Layout::new(REFERENCE);
+        assert_eq!(layout.scale, 1.0);
+        assert_eq!(layout.font_size(), 16);
+        assert_eq!(
+            layout.tiles(&PAGE),
+            vec![
+                [25.0, 578.5, 320.0, 180.0],
+                [372.5, 578.5, 320.0, 180.0],
+                [720.0, 540.0, 480.0, 270.0],
+                [1227.5, 578.5, 320.0, 180.0],
+                [1575.0, 578.5, 320.0, 180.0],
+            ]
+        );
+        let large = layout.tiles(&PAGE)[2];
+        assert_eq!(
+            layout.heading(large),
+            TextArea {
+                x: 760.0,
+                y: 500.0,
+                width: 440.0
+            }
+        );
+        assert_eq!(
+            layout.subheading(large),
+            TextArea {
+                x: 720.0,
+                y: 915.0,
+                width: 480.0
+            }
+        );
+        assert_eq!(
+            layout.caption(large),
+            TextArea {
+                x: 736.0,
+                y: 572.0,
+                width: 448.0
+            }
+        );
+        assert_eq!(
+            layout.decisions(large),
+            TextArea {
+                x: 720.0,
+                y: 864.0,
+                width: 480.0
+            }
+        );
+        let strip = layout.scoreboard(large);
+        assert_eq!(strip, [720.0, 810.0, 480.0, 32.0]);
+        assert_eq!(
+            layout.scoreboard_text(strip),
+            TextArea {
+                x: 728.0,
+                y: 832.0,
+                width: 464.0
+            }
+        );
+        assert_eq!(
+            layout.day(large),
+            TextArea {
+                x: 720.0,
+                y: 440.0,
+                width: 480.0
+            }
+        );
+        let small = layout.tiles(&PAGE)[1];
+        assert_eq!(layout.separator(small, large), [705.25, 424.0, 2.0, 418.0]);
+        assert_eq!(layout.right_arrow(), [1664.0, 0.0, 256.0, 256.0]);
+    }
+
+    #[test]
+    fn grid_cells() {
+        let layout = Layout::new(REFERENCE);
+        let grid = layout.grid([27.5, 200.0], 3, 13);
+        assert_eq!(grid, [27.5, 200.0, 560.0, 84.0]);
+        assert_eq!(layout.grid_cell(grid, 0, 0), [35.5, 220.0]);
+        assert_eq!(layout.grid_cell(grid, 2, 1), [107.5, 276.0]);
+        assert_eq!(layout.grid_cell(grid, 1, 12), [547.5, 248.0]);
+        // Halving the window halves everything, apart from where the grid was put.
+        let layout = Layout::new([960.0, 540.0]);
+        let grid = layout.grid([0.0, 0.0], 3, 13);
+        assert_eq!(grid, [0.0, 0.0, 280.0, 42.0]);
+        assert_eq!(layout.grid_cell(grid, 2, 1), [40.0, 38.0]);
+    }
+
+    #[test]
+    fn scales_down_uniformly() {
+        let layout = Layout::new([960.0, 540.0]);
+        assert_eq!(layout.scale, 0.5);
+        assert_eq!(layout.font_size(), 8);
+        assert_eq!(
+            layout.tiles(&[Tile::Large])[0],
+            [360.0, 270.0, 240.0, 135.0]
+        );
+        assert_eq!(layout.right_arrow(), [832.0, 0.0, 128.0, 128.0]);
+    }
+
+    #[test]
+    fn fits_any_page_size() {
+        let layout = Layout::new(REFERENCE);
+        // Small pages sit in the middle, at full size.
+        assert_eq!(
+            layout.tiles(&[Tile::Small, Tile::Large]),
+            vec![[546.25, 578.5, 320.0, 180.0], [893.75, 540.0, 480.0, 270.0]]
+        );
+        for size in 1..=40 {
+            let mut page = vec![Tile::Small; size];
+            page[size / 2] = Tile::Large;
+            let tiles = layout.tiles(&page);
+            assert_eq!(tiles.len(), size);
+            let left = tiles[0][0];
+            let right = REFERENCE[0] - (tiles[size - 1][0] + tiles[size - 1][2]);
+            assert!((left - right).abs() < 1e-9, "{} {} {}", size, left, right);
+            assert!(left >= PADDING / 2.0 - 1e-9, "{} {}", size, left);
+            assert!(tiles
+                .windows(2)
+                .all(|pair| pair[0][0] + pair[0][2] < pair[1][0]));
+        }
+        assert!(layout.tiles(&[]).is_empty());
+    }
+
+    #[test]
+    fn letterboxes_other_aspect_ratios() {
+        // A 4:3 window is limited by its width, so the design is centered vertically.
+        let layout = Layout::new([1024.0, 768.0]);
+        assert_eq!(layout.scale, 1024.0 / 1920.0);
+        assert_eq!(layout.fullscreen(), [0.0, 0.0, 1024.0, 768.0]);
+        let notice = layout.notice();
+        assert_eq!(notice.y, (768.0 - 576.0) / 2.0 + 500.0 * layout.scale);
+        let tiles = layout.tiles(&PAGE);
+        let last = tiles[4];
+        assert!(last[0] + last[2] <= 1024.0);
+    }
+}
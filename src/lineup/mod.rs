@@ -1,149 +1,1216 @@
-use image::{RgbaImage, ImageFormat};
-use crate::api;
+use crate::paging::{self, Pager};
+use crate::task::{self, Task};
+use crate::{api, client, images};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 static MLB_LOGO_LARGE_BYTES: &[u8] = include_bytes!("../../assets/mlb_logo_large.jpg");
 static MLB_LOGO_SMALL_BYTES: &[u8] = include_bytes!("../../assets/mlb_logo_small.jpg");
 
 lazy_static! {
-    static ref MLB_LOGO_LARGE: RgbaImage =
-        image::load_from_memory_with_format(MLB_LOGO_LARGE_BYTES, ImageFormat::JPEG)
-            .unwrap()
-            .into_rgba();
-    static ref MLB_LOGO_SMALL: RgbaImage =
-        image::load_from_memory_with_format(MLB_LOGO_SMALL_BYTES, ImageFormat::JPEG)
-            .unwrap()
-            .into_rgba();
+    static ref UNAVAILABLE_LARGE: RgbaImage = placeholder(480, 270);
+    static ref UNAVAILABLE_SMALL: RgbaImage = placeholder(320, 180);
+    static ref MLB_LOGO_LARGE: RgbaImage = images::decode(None, MLB_LOGO_LARGE_BYTES).unwrap();
+    static ref MLB_LOGO_SMALL: RgbaImage = images::decode(None, MLB_LOGO_SMALL_BYTES).unwrap();
 }
 
+/// Draws the stand-in for photos that could not be downloaded: a plain, dark frame with a
+/// lighter border, which is easy enough to tell apart from the MLB logo that stands in while
+/// photos are still loading.
+fn placeholder(width: u32, height: u32) -> RgbaImage {
+    const BORDER: u32 = 4;
+    RgbaImage::from_fn(width, height, |x, y| {
+        if x < BORDER || y < BORDER || x >= width - BORDER || y >= height - BORDER {
+            image::Rgba([96, 96, 96, 255])
+        } else {
+            image::Rgba([32, 32, 32, 255])
+        }
+    })
+}
+
+/// The games of one or more days, in order. The cursor runs straight across all of them, from
+/// the first game of the first day to the last game of the last day.
 pub struct Schedule {
     pub games: Vec<Game>,
-    cursor: usize,
+    /// Each day along with how many of the games belong to it, in the same order as the games.
+    days: Vec<(NaiveDate, usize)>,
+    pager: Pager,
 }
 
 impl Schedule {
-    const PAGE_SIZE: usize = 5;
-
     pub fn left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
-        }
+        self.pager.left();
     }
 
     pub fn right(&mut self) {
-        if self.cursor < self.games.len() - 2 {
-            self.cursor += 1;
-        }
+        self.pager.right();
+    }
+
+    /// Swaps in different paging behavior, keeping the cursor where it is.
+    pub fn with_paging(mut self, settings: paging::Settings) -> Schedule {
+        let cursor = self.pager.cursor();
+        self.pager = Pager::new(settings, self.games.len());
+        self.pager.focus(cursor);
+        self
+    }
+
+    /// The games of each day, including any days without games.
+    pub fn days(&self) -> Vec<(NaiveDate, &[Game])> {
+        let mut start = 0;
+        self.days
+            .iter()
+            .map(|(date, count)| {
+                let games = &self.games[start..start + count];
+                start += count;
+                (*date, games)
+            })
+            .collect()
+    }
+
+    /// The day that the game at the given index belongs to.
+    fn date_of(&self, index: usize) -> Option<NaiveDate> {
+        let mut end = 0;
+        self.days().into_iter().find_map(|(date, games)| {
+            end += games.len();
+            Some(date).filter(|_| index < end)
+        })
+    }
+
+    /// The game that the cursor is on, if there are any games at all.
+    pub fn focused(&self) -> Option<&Game> {
+        self.games.get(self.pager.cursor())
     }
 
     pub fn has_more(&self) -> bool {
-        self.cursor < self.games.len() - Self::PAGE_SIZE
+        self.pager.has_more()
     }
 
     pub fn has_less(&self) -> bool {
-        self.cursor > Self::PAGE_SIZE - 1
+        self.pager.has_less()
     }
 
-    /// Returns the list of game snippets for the current page. Each page has five games on it.
+    /// Returns the list of game snippets for the current page, which is five games by default.
     ///
     /// E.G. If, there are are 14 games and we are focusing on game index 7, then this function will
     /// return games indices 5, 6, 7, 8, and 9 with 7 being the Snippet::Large variant.
-    pub fn page(&mut self) -> Vec<Snippet> {
-        let page = self.cursor / Self::PAGE_SIZE;
-        // The left most snippet of this page.
-        let left = page * Self::PAGE_SIZE;
-        // The right end of the page can fall off if the map if we're on the last page.
-        let right = match left + Self::PAGE_SIZE {
-            right if right < self.games.len() - 1 => right,
-            _ => self.games.len() - 1,
-        };
+    pub fn page(&mut self) -> Vec<Snippet<'_>> {
+        let range = self.pager.page();
+        let (left, right) = (range.start, range.end);
         // The cursor may be 7, but the focus of this page is index 2.
-        let page_focus = self.cursor % Self::PAGE_SIZE;
+        let page_focus = self.pager.cursor() - left;
+        // The first game on the page says which day it's from, as does the first game of every
+        // day after that.
+        let days: Vec<Option<NaiveDate>> = (left..right)
+            .map(|index| match (index, self.date_of(index)) {
+                (index, date) if index == left || date != self.date_of(index - 1) => date,
+                _ => None,
+            })
+            .collect();
         // Sorry the extra parenthesis here, rustc thought that we were returning a &mut rather
         // than accessing self.games as a &mut.
         (&mut self.games)[left..right]
             .iter_mut()
+            .zip(days)
             .enumerate()
-            .map(|(index, game)| {
+            .map(|(index, (game, day))| {
                 if index == page_focus {
                     // If the underlying resource hasn't come in over the network yet, then this
-                    // is the point where we decide to default to the appropriate size of the MLB logo.
-                    Snippet::Large(
-                        game.large.get().unwrap_or(&*MLB_LOGO_LARGE),
-                        game.headline.as_str(),
-                        game.subhead.as_str(),
-                    )
+                    // is the point where we decide to default to the appropriate size of the MLB
+                    // logo. If it never will come in, then we say as much with a placeholder.
+                    Snippet::Large {
+                        id: game.id,
+                        picture: picture(
+                            &mut game.large,
+                            Picture::asset("mlb_logo_large", &MLB_LOGO_LARGE),
+                            Picture::asset("unavailable_large", &UNAVAILABLE_LARGE),
+                        ),
+                        scoreboard: Scoreboard {
+                            away: &game.away,
+                            home: &game.home,
+                            state: &game.state,
+                        },
+                        decisions: &game.decisions,
+                        headline: game.headline.as_str(),
+                        subhead: game.subhead.as_str(),
+                        warnings: game.warnings.as_slice(),
+                        day,
+                    }
                 } else {
-                    Snippet::Small(game.small.get().unwrap_or(&*MLB_LOGO_SMALL))
+                    Snippet::Small {
+                        id: game.id,
+                        picture: picture(
+                            &mut game.small,
+                            Picture::asset("mlb_logo_small", &MLB_LOGO_SMALL),
+                            Picture::asset("unavailable_small", &UNAVAILABLE_SMALL),
+                        ),
+                        scoreboard: Scoreboard {
+                            away: &game.away,
+                            home: &game.home,
+                            state: &game.state,
+                        },
+                        day,
+                    }
                 }
             })
             .collect::<Vec<Snippet>>()
     }
 }
 
+/// Either variant carries the gamePk of its game, and the day that its game is from if a header
+/// for the day belongs above it (which is the case for the first game of the page and the first
+/// game of each day).
 pub enum Snippet<'a> {
-    Small(&'a RgbaImage),
-    Large(&'a RgbaImage, &'a str, &'a str),
+    Small {
+        id: u64,
+        picture: Picture<'a>,
+        scoreboard: Scoreboard<'a>,
+        day: Option<NaiveDate>,
+    },
+    Large {
+        id: u64,
+        picture: Picture<'a>,
+        scoreboard: Scoreboard<'a>,
+        decisions: &'a Decisions,
+        headline: &'a str,
+        subhead: &'a str,
+        warnings: &'a [String],
+        day: Option<NaiveDate>,
+    },
+}
+
+impl Snippet<'_> {
+    pub fn id(&self) -> u64 {
+        match self {
+            Snippet::Small { id, .. } | Snippet::Large { id, .. } => *id,
+        }
+    }
+
+    pub fn day(&self) -> Option<NaiveDate> {
+        match self {
+            Snippet::Small { day, .. } | Snippet::Large { day, .. } => *day,
+        }
+    }
 }
 
-impl From<api::Schedule> for Schedule {
-    fn from(mut schedule: api::Schedule) -> Self {
+/// Who is playing and how it's going, in as few characters as it takes. E.G.
+///
+/// ```text
+/// TB 3  @  BAL 4   Final
+/// NYM  @  NYY   7:05 PM
+/// ```
+pub struct Scoreboard<'a> {
+    pub away: &'a Team,
+    pub home: &'a Team,
+    pub state: &'a State,
+}
+
+impl std::fmt::Display for Scoreboard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let side = |team: &Team| match team.score {
+            Some(score) => format!("{} {}", team.abbreviation, score),
+            None => team.abbreviation.clone(),
+        };
+        f.write_fmt(format_args!(
+            "{}  @  {}   {}",
+            side(self.away),
+            side(self.home),
+            self.state
+        ))
+    }
+}
+
+/// Games that don't have a photo at all get the MLB logo, same as those whose photo is still
+/// on its way.
+fn picture<'a>(
+    photo: &'a mut Option<Photo>,
+    loading: Picture<'static>,
+    unavailable: Picture<'static>,
+) -> Picture<'a> {
+    match photo {
+        Some(photo) => photo.picture(loading, unavailable),
+        None => loading,
+    }
+}
+
+/// An image along with a key that uniquely identifies it, so that whoever is rendering it can
+/// hang on to whatever they have derived from it (such as a texture) for as long as the key
+/// keeps coming back.
+pub struct Picture<'a> {
+    pub key: ImageKey,
+    pub image: &'a RgbaImage,
+    /// Set if this is a stand-in for a photo that could not be downloaded.
+    pub failure: Option<&'a api::APIError>,
+}
+
+impl Picture<'static> {
+    fn asset(name: &'static str, image: &'static RgbaImage) -> Picture<'static> {
+        Picture {
+            key: ImageKey::Asset(name),
+            image,
+            failure: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageKey {
+    /// One of the images that is compiled into the binary.
+    Asset(&'static str),
+    /// A photo that was downloaded for a game.
+    Photo(u64),
+}
+
+impl Schedule {
+    /// Builds the lineup out of the given schedule, kicking off the downloads of its photos.
+    /// The schedule may cover any number of days, including none at all.
+    pub fn new(schedule: api::Schedule, photos: &PhotoSource) -> Schedule {
+        let (games, days) = Schedule::flatten(schedule, |game| Game::new(game, photos));
+        Schedule {
+            pager: Pager::new(paging::Settings::default(), games.len()),
+            games,
+            days,
+        }
+    }
+
+    /// Lines up the games of every day one after the other.
+    fn flatten<F: FnMut(api::Game) -> Game>(
+        schedule: api::Schedule,
+        mut game: F,
+    ) -> (Vec<Game>, Vec<(NaiveDate, usize)>) {
         let mut games = vec![];
-        for game in schedule.dates.pop().unwrap().games.into_iter() {
-            games.push(Game {
-                headline: game.content.editorial.recap.home.headline.clone(),
-                subhead: game.content.editorial.recap.home.subhead.clone(),
-                large: Photo::new(game.content.editorial.recap.home.photo.cuts.large.src),
-                small: Photo::new(game.content.editorial.recap.home.photo.cuts.small.src),
+        let mut days = vec![];
+        for date in schedule.dates {
+            days.push((date.date, date.games.len()));
+            games.extend(date.games.into_iter().map(&mut game));
+        }
+        (games, days)
+    }
+
+    /// Brings the lineup up to date with a fresher copy of the same schedule.
+    ///
+    /// Games are matched up by their gamePk, so the cursor stays on whichever game it was on and
+    /// photos that have already come in (or are on their way) aren't downloaded all over again.
+    ///
+    /// Returns the keys of the photos that didn't make it through the merge, as whatever was
+    /// uploaded for them will never be drawn again.
+    pub fn merge(&mut self, schedule: api::Schedule, photos: &PhotoSource) -> Vec<ImageKey> {
+        let focused = self.focused().map(|game| game.id);
+        let mut existing: HashMap<u64, Game> =
+            self.games.drain(..).map(|game| (game.id, game)).collect();
+        let mut dropped = vec![];
+        let (games, days) =
+            Schedule::flatten(schedule, |game| match existing.remove(&game.game_pk) {
+                Some(mut existing) => {
+                    dropped.extend(existing.merge(game, photos));
+                    existing
+                }
+                None => Game::new(game, photos),
             });
+        // Along with every photo of the games that have gone from the schedule altogether.
+        dropped.extend(existing.values().flat_map(Game::photo_keys));
+        self.games = games;
+        self.days = days;
+        // Should the focused game have somehow disappeared, then stay put as best we can.
+        self.pager.resize(self.games.len());
+        if let Some(index) = focused.and_then(|id| self.games.iter().position(|game| game.id == id))
+        {
+            self.pager.focus(index);
+        }
+        dropped
+    }
+
+    /// How long to wait in between refreshes of this lineup, if it's worth refreshing at all.
+    /// Games that are under way are refreshed the most often, and there's nothing left to say
+    /// about a day once all of its games are final.
+    pub fn refresh_interval(&self, settings: &RefreshSettings) -> Option<Duration> {
+        let states = || self.games.iter().map(|game| &game.state);
+        if states().any(|state| matches!(state, State::InProgress(_))) {
+            Some(settings.live_interval)
+        } else if states().any(|state| *state != State::Final) {
+            Some(settings.interval)
+        } else {
+            None
         }
-        Schedule { games, cursor: 0 }
     }
 }
 
 pub struct Game {
+    /// statsapi's gamePk.
+    pub id: u64,
     pub headline: String,
     pub subhead: String,
-    large: Photo,
-    small: Photo,
+    /// The recap itself, as HTML. Empty if there isn't one.
+    pub body: String,
+    /// Whatever was missing from the schedule for this game. None of it is reason enough to
+    /// leave the game out of the lineup, but it's worth telling the user about.
+    pub warnings: Vec<String>,
+    pub away: Team,
+    pub home: Team,
+    pub state: State,
+    pub decisions: Decisions,
+    /// Where the game is being played, if the schedule says.
+    pub venue: Option<String>,
+    large: Option<Photo>,
+    small: Option<Photo>,
+}
+
+/// One side of a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Team {
+    pub name: String,
+    pub abbreviation: String,
+    /// Runs scored so far, if the game has started.
+    pub score: Option<u32>,
+    /// The team's record going into (or coming out of, if it's over) the game.
+    pub record: Option<api::Record>,
+    pub winner: bool,
+}
+
+/// The names of the pitchers of record, once the game is over.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Decisions {
+    pub winner: Option<String>,
+    pub loser: Option<String>,
+    pub save: Option<String>,
+}
+
+impl From<&api::Decisions> for Decisions {
+    fn from(decisions: &api::Decisions) -> Decisions {
+        let name =
+            |person: &Option<api::Person>| person.as_ref().map(|person| person.full_name.clone());
+        Decisions {
+            winner: name(&decisions.winner),
+            loser: name(&decisions.loser),
+            save: name(&decisions.save),
+        }
+    }
+}
+
+/// E.G. "W: Kevin Gausman   L: Sergio Romo   SV: Zach Britton", leaving out whoever is missing.
+impl std::fmt::Display for Decisions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let decisions: Vec<String> = [("W", &self.winner), ("L", &self.loser), ("SV", &self.save)]
+            .iter()
+            .filter_map(|(label, name)| name.as_ref().map(|name| format!("{}: {}", label, name)))
+            .collect();
+        f.write_str(&decisions.join("   "))
+    }
+}
+
+/// Where a game is at, boiled down from statsapi's status and linescore.
+#[derive(Clone, Debug, PartialEq)]
+pub enum State {
+    /// Hasn't started yet, and is due to at the given time.
+    Scheduled(DateTime<Utc>),
+    /// Under way, along with which inning it is in (E.G. "Top 7th") if that is known.
+    InProgress(Option<String>),
+    Final,
+    /// Anything out of the ordinary, such as a postponement or a delay, in statsapi's words.
+    Other(String),
+}
+
+impl State {
+    pub fn new(
+        status: &api::Status,
+        linescore: Option<&api::Linescore>,
+        start: DateTime<Utc>,
+    ) -> State {
+        match (
+            status.abstract_game_state.as_str(),
+            status.detailed_state.as_str(),
+        ) {
+            ("Preview", "Scheduled") | ("Preview", "Pre-Game") | ("Live", "Warmup") => {
+                State::Scheduled(start)
+            }
+            ("Live", "In Progress") => State::InProgress(linescore.and_then(State::inning)),
+            ("Final", "Final") | ("Final", "Game Over") => State::Final,
+            (_, detailed) => match &status.reason {
+                Some(reason) => State::Other(format!("{}: {}", detailed, reason)),
+                None => State::Other(detailed.to_string()),
+            },
+        }
+    }
+
+    fn inning(linescore: &api::Linescore) -> Option<String> {
+        let ordinal = match (&linescore.current_inning_ordinal, linescore.current_inning) {
+            (Some(ordinal), _) => ordinal.clone(),
+            (None, Some(inning)) => format!("inning {}", inning),
+            (None, None) => return None,
+        };
+        Some(match &linescore.inning_state {
+            Some(state) => format!("{} {}", state, ordinal),
+            None => ordinal,
+        })
+    }
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            // Start times are given in UTC, which is of no use to anyone watching.
+            State::Scheduled(start) => f.write_fmt(format_args!(
+                "{}",
+                start.with_timezone(&Local).format("%-I:%M %p")
+            )),
+            State::InProgress(Some(inning)) => f.write_str(inning),
+            State::InProgress(None) => f.write_str("In Progress"),
+            State::Final => f.write_str("Final"),
+            State::Other(state) => f.write_str(state),
+        }
+    }
+}
+
+impl From<api::Side> for Team {
+    fn from(side: api::Side) -> Team {
+        let api::Team {
+            name,
+            abbreviation,
+            team_name,
+        } = side.team;
+        // Teams only come with abbreviations when they've been hydrated, so fall back on the
+        // short name (E.G. "Orioles") or, failing that, the full name.
+        let abbreviation = abbreviation.or(team_name).unwrap_or_else(|| name.clone());
+        Team {
+            name,
+            abbreviation,
+            score: side.score,
+            record: side.league_record,
+            winner: side.is_winner.unwrap_or(false),
+        }
+    }
+}
+
+impl Game {
+    pub fn new(game: api::Game, photos: &PhotoSource) -> Game {
+        Game::with_photos(game, &mut |src| Photo::new(src.to_string(), photos.clone()))
+    }
+
+    /// Brings the game up to date with a fresher copy of it. The photos are kept as long as the
+    /// recap still points at them.
+    pub fn merge(&mut self, game: api::Game, photos: &PhotoSource) -> Vec<ImageKey> {
+        let mut kept = [self.large.take(), self.small.take()];
+        *self = Game::with_photos(game, &mut |src| {
+            kept.iter_mut()
+                .find(|photo| matches!(photo, Some(photo) if photo.src == src))
+                .and_then(Option::take)
+                .unwrap_or_else(|| Photo::new(src.to_string(), photos.clone()))
+        });
+        // Anything still left over has been replaced (or taken out of the recap).
+        kept.iter().flatten().map(Photo::key).collect()
+    }
+
+    /// The keys of the game's photos, whether or not they've been drawn yet.
+    fn photo_keys(&self) -> Vec<ImageKey> {
+        [&self.large, &self.small]
+            .iter()
+            .filter_map(|photo| photo.as_ref().map(Photo::key))
+            .collect()
+    }
+
+    /// Builds the game, getting its photos from the given function.
+    fn with_photos(game: api::Game, photos: &mut dyn FnMut(&str) -> Photo) -> Game {
+        let mut warnings = vec![];
+        // If nobody has written up the game yet (or they forgot the headline), then the best
+        // that we can do for a headline is to say who is playing.
+        let matchup = format!(
+            "{} at {}",
+            game.teams.away.team.name, game.teams.home.team.name
+        );
+        let (headline, subhead, body, large, small) = match game.recap() {
+            Some(home) => {
+                let cuts = home.photo.as_ref().map(|photo| &photo.cuts);
+                let mut photo = |cut: Option<&api::Photo>, size: &str| match cut
+                    .and_then(|cut| cut.src.as_ref())
+                {
+                    Some(src) => Some(photos(src)),
+                    None => {
+                        warnings.push(format!("The recap has no {} photo", size));
+                        None
+                    }
+                };
+                let large = photo(cuts.and_then(|cuts| cuts.large.as_ref()), "480x270");
+                let small = photo(cuts.and_then(|cuts| cuts.small.as_ref()), "320x180");
+                let headline = home.headline.clone().unwrap_or_else(|| {
+                    warnings.push("The recap has no headline".to_string());
+                    matchup
+                });
+                (
+                    headline,
+                    home.subhead.clone(),
+                    home.body.clone(),
+                    large,
+                    small,
+                )
+            }
+            None => {
+                warnings.push("No recap has been published for this game yet".to_string());
+                (matchup, String::new(), String::new(), None, None)
+            }
+        };
+        Game {
+            id: game.game_pk,
+            headline,
+            subhead,
+            body,
+            warnings,
+            away: game.teams.away.into(),
+            home: game.teams.home.into(),
+            state: State::new(&game.status, game.linescore.as_ref(), game.game_date),
+            decisions: game
+                .decisions
+                .as_ref()
+                .map(Decisions::from)
+                .unwrap_or_default(),
+            venue: game.venue.map(|venue| venue.name),
+            large,
+            small,
+        }
+    }
+}
+
+/// Every Photo gets its own ID, which is never reused over the lifetime of the process.
+static PHOTO_IDS: AtomicU64 = AtomicU64::new(0);
+
+/// The number of times that a photo is requested before giving up on it.
+static PHOTO_ATTEMPTS: u32 = 4;
+
+/// How long a photo has to arrive in, across all of its attempts and the backoff in between.
+static PHOTO_DEADLINE: Duration = Duration::from_secs(90);
+
+/// How long a schedule has to arrive in, no matter where it is coming from.
+static SCHEDULE_DEADLINE: Duration = Duration::from_secs(45);
+
+pub enum PhotoState {
+    Loading,
+    Loaded(RgbaImage),
+    Failed(api::APIError),
+}
+
+/// Where photos come from.
+#[derive(Clone)]
+pub enum PhotoSource {
+    /// Downloaded from wherever the schedule says that they live, through the given client.
+    Http(client::Client),
+    /// Read out of a local directory. Each photo is looked up by the file name at the end of
+    /// its URL, so a directory of previously downloaded photos can be dropped in as is.
+    Directory(PathBuf),
 }
 
 pub struct Photo {
-    photo: Option<RgbaImage>,
-    channel: crossbeam_channel::Receiver<RgbaImage>,
+    id: u64,
+    src: String,
+    state: PhotoState,
+    // Dropping the Photo (say, because the user has moved on to another day) cancels the
+    // download, as there's no sense in tying up a connection on something nobody will see.
+    download: Task<api::APIResult<RgbaImage>>,
 }
 
 impl Photo {
-    pub fn new(src: String) -> Photo {
-        let (tx, rx) = crossbeam_channel::bounded(1);
-        tokio::task::spawn(async move {
-            let https = hyper_tls::HttpsConnector::new();
-            let resp = hyper::Client::builder()
-                .build::<_, hyper::Body>(https)
-                .get(src.parse().unwrap())
-                .await
-                .unwrap();
-            let buf = hyper::body::to_bytes(resp).await.unwrap();
-            let img = image::load_from_memory_with_format(&buf, ImageFormat::JPEG)
-                .unwrap()
-                .into_rgba();
-            tx.send(img).unwrap();
+    pub fn new(src: String, source: PhotoSource) -> Photo {
+        let url = src.clone();
+        let download = Task::spawn(async move {
+            let attempts = async {
+                let mut attempt = 0;
+                loop {
+                    attempt += 1;
+                    let result = match &source {
+                        PhotoSource::Http(client) => Photo::download(client, &src).await,
+                        PhotoSource::Directory(dir) => Photo::read(&src, dir).await,
+                    };
+                    match result {
+                        Err(_) if attempt < PHOTO_ATTEMPTS => {
+                            tokio::time::delay_for(backoff(attempt)).await
+                        }
+                        result => break result,
+                    }
+                }
+            };
+            task::deadline(&src, PHOTO_DEADLINE, attempts).await
         });
         Photo {
-            photo: None,
-            channel: rx,
+            id: PHOTO_IDS.fetch_add(1, Ordering::Relaxed),
+            src: url,
+            state: PhotoState::Loading,
+            download,
         }
     }
 
-    pub fn get(&mut self) -> Option<&RgbaImage> {
-        if  self.photo.is_some() {
-            return self.photo.as_ref();
+    async fn download(client: &client::Client, src: &str) -> api::APIResult<RgbaImage> {
+        let resp = client.get(src).await?;
+        images::decode(resp.content_type.as_deref(), &resp.body)
+            .map_err(|err| api::APIError::new(src, api::ErrorContext::Decoding, err))
+    }
+
+    async fn read(src: &str, dir: &std::path::Path) -> api::APIResult<RgbaImage> {
+        let name = src
+            .split('?')
+            .next()
+            .unwrap_or(src)
+            .rsplit('/')
+            .next()
+            .unwrap_or(src);
+        let path = dir.join(name);
+        let buf = tokio::fs::read(&path).await.map_err(|err| {
+            api::APIError::new(path.display().to_string(), api::ErrorContext::Reading, err)
+        })?;
+        images::decode(None, &buf).map_err(|err| {
+            api::APIError::new(path.display().to_string(), api::ErrorContext::Decoding, err)
+        })
+    }
+
+    /// What the photo's texture is cached under.
+    pub fn key(&self) -> ImageKey {
+        ImageKey::Photo(self.id)
+    }
+
+    /// Checks in on the download and returns where it is at.
+    pub fn state(&mut self) -> &PhotoState {
+        if let PhotoState::Loading = self.state {
+            match self.download.poll() {
+                Some(Ok(image)) => self.state = PhotoState::Loaded(image),
+                Some(Err(err)) => self.state = PhotoState::Failed(err),
+                None => (),
+            }
+        }
+        &self.state
+    }
+
+    /// Returns the downloaded photo, or the appropriate stand-in if it is still on its way
+    /// (or is never going to arrive).
+    pub fn picture<'a>(
+        &'a mut self,
+        loading: Picture<'static>,
+        unavailable: Picture<'static>,
+    ) -> Picture<'a> {
+        let key = self.key();
+        match self.state() {
+            PhotoState::Loading => loading,
+            PhotoState::Loaded(image) => Picture {
+                key,
+                image,
+                failure: None,
+            },
+            PhotoState::Failed(err) => Picture {
+                failure: Some(err),
+                ..unavailable
+            },
+        }
+    }
+}
+
+/// A schedule that is being fetched in the background. Dropping the Loader cancels the fetch,
+/// just like with Photo.
+pub struct Loader {
+    query: api::ScheduleQuery,
+    fetch: Task<api::APIResult<api::Schedule>>,
+    photos: PhotoSource,
+}
+
+impl Loader {
+    pub fn new(sources: &Sources, query: api::ScheduleQuery) -> Loader {
+        let fetch = sources.schedule.fetch(&query);
+        let src = query.to_string();
+        Loader {
+            query,
+            fetch: Task::spawn(async move { task::deadline(&src, SCHEDULE_DEADLINE, fetch).await }),
+            photos: sources.photos.clone(),
         }
-        match self.channel.try_recv() {
-            Ok(image) => {
-                self.photo = Some(image);
-                self.photo.as_ref()
+    }
+
+    pub fn query(&self) -> &api::ScheduleQuery {
+        &self.query
+    }
+
+    /// Returns the fetched schedule once it has arrived, None while it is still in flight.
+    pub fn poll(&mut self) -> Option<api::APIResult<Schedule>> {
+        let photos = &self.photos;
+        self.fetch
+            .poll()
+            .map(|result| result.map(|schedule| Schedule::new(schedule, photos)))
+    }
+}
+
+/// How often the lineup is refreshed while it's on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RefreshSettings {
+    /// For days that still have games to come.
+    pub interval: Duration,
+    /// For days that have games under way, whose scores can change at any moment.
+    pub live_interval: Duration,
+}
+
+impl Default for RefreshSettings {
+    fn default() -> RefreshSettings {
+        RefreshSettings {
+            interval: Duration::from_secs(300),
+            live_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Keeps the lineup of a day up to date by fetching its schedule again in the background every
+/// so often and merging in whatever has changed. Dropping the Refresher cancels any refresh
+/// that is in flight.
+pub struct Refresher {
+    query: api::ScheduleQuery,
+    settings: RefreshSettings,
+    /// When the lineup was last brought up to date.
+    refreshed_at: Instant,
+    fetch: Option<Task<api::APIResult<api::Schedule>>>,
+    /// What day it is. Only lineups that take in today get refreshed, as the scores from any
+    /// other day aren't going anywhere (and a postponed game from last week is never going to
+    /// get played on the day it says). This is asked every time, as the application could well
+    /// be left running overnight.
+    today: fn() -> NaiveDate,
+}
+
+impl Refresher {
+    /// The schedule for the given query is assumed to have only just been fetched.
+    pub fn new(query: api::ScheduleQuery, settings: RefreshSettings) -> Refresher {
+        Refresher {
+            query,
+            settings,
+            refreshed_at: Instant::now(),
+            fetch: None,
+            today: || chrono::Local::now().date_naive(),
+        }
+    }
+
+    /// Merges in a refresh if one has arrived, or kicks off the next one if it is due. Returns
+    /// the keys of any photos that the merge let go of (see Schedule::merge).
+    pub fn poll(&mut self, sources: &Sources, schedule: &mut Schedule) -> Vec<ImageKey> {
+        let mut dropped = vec![];
+        if let Some(fetch) = &mut self.fetch {
+            match fetch.poll() {
+                Some(result) => {
+                    // A failed refresh isn't worth bothering the user over, as the lineup that
+                    // they already have is still perfectly good. We'll just try again later.
+                    if let Ok(fresh) = result {
+                        dropped = schedule.merge(fresh, &sources.photos);
+                    }
+                    self.fetch = None;
+                    self.refreshed_at = Instant::now();
+                }
+                None => return dropped,
+            }
+        }
+        if !self.query.includes((self.today)()) {
+            return dropped;
+        }
+        match schedule.refresh_interval(&self.settings) {
+            Some(interval) if self.refreshed_at.elapsed() >= interval => {
+                let fetch = sources.schedule.fetch(&self.query);
+                let src = self.query.to_string();
+                self.fetch = Some(Task::spawn(async move {
+                    task::deadline(&src, SCHEDULE_DEADLINE, fetch).await
+                }));
             }
-            _ => None,
+            _ => (),
         }
+        dropped
     }
-}
\ No newline at end of file
+}
+
+/// Where the schedule and its photos get loaded from.
+#[derive(Clone)]
+pub struct Sources {
+    pub schedule: Arc<dyn api::ScheduleSource>,
+    pub photos: PhotoSource,
+}
+
+/// How long to wait before making the given retry attempt (counting from 1). The delay doubles
+/// with each attempt, starting from one second and leveling off at a minute.
+pub fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt.saturating_sub(1)).min(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn loads_from_a_stand_in_server() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let sources = Sources {
+                schedule: Arc::new(api::HttpSource::at(
+                    client::Client::default(),
+                    api::testing::stand_in(api::testing::TEST_DATA),
+                )),
+                photos: PhotoSource::Directory(PathBuf::from("assets")),
+            };
+            let date = NaiveDate::from_ymd_opt(2018, 6, 10).unwrap();
+            let mut loader = Loader::new(&sources, api::ScheduleQuery::new(date));
+            let schedule = loop {
+                match loader.poll() {
+                    Some(result) => break result.unwrap(),
+                    None => tokio::time::delay_for(Duration::from_millis(10)).await,
+                }
+            };
+            assert_eq!(schedule.games.len(), 7);
+            assert_eq!(
+                schedule.games[0].headline,
+                "Machado, Schoop lift O's past Rays"
+            );
+            assert_eq!(
+                schedule.games[6].headline,
+                "New York Mets at New York Yankees"
+            );
+            let first = &schedule.games[0];
+            assert_eq!(first.id, 530781);
+            assert_eq!(
+                (first.away.abbreviation.as_str(), first.away.score),
+                ("TB", Some(3))
+            );
+            assert_eq!(
+                (first.home.abbreviation.as_str(), first.home.score),
+                ("BAL", Some(4))
+            );
+            assert!(first.home.winner && !first.away.winner);
+            assert_eq!(first.state, State::Final);
+            assert_eq!(
+                first.decisions.to_string(),
+                "W: Kevin Gausman   L: Sergio Romo   SV: Zach Britton"
+            );
+            assert_eq!(schedule.games[6].decisions, Decisions::default());
+            assert_eq!(schedule.games[6].decisions.to_string(), "");
+            assert_eq!(
+                Scoreboard {
+                    away: &first.away,
+                    home: &first.home,
+                    state: &first.state
+                }
+                .to_string(),
+                "TB 3  @  BAL 4   Final"
+            );
+            assert_eq!(
+                schedule.games[6].state,
+                State::Other("Postponed: Rain".to_string())
+            );
+            assert_eq!(first.venue.as_deref(), Some("Oriole Park at Camden Yards"));
+        });
+    }
+
+    #[test]
+    fn games_without_recaps() {
+        let game = |json: &str| {
+            Game::new(
+                serde_json::from_str(json).unwrap(),
+                &PhotoSource::Directory(PathBuf::from("assets")),
+            )
+        };
+        let teams = r#""gamePk": 530787,
+            "gameDate": "2018-06-10T23:05:00Z",
+            "status": {"abstractGameState": "Final", "detailedState": "Postponed"},
+            "teams": {
+                "away": {"team": {"id": 121, "name": "New York Mets"}},
+                "home": {"team": {"id": 147, "name": "New York Yankees", "teamName": "Yankees"}}
+            }"#;
+        let postponed = game(&format!("{{{}}}", teams));
+        assert_eq!(postponed.headline, "New York Mets at New York Yankees");
+        assert_eq!(postponed.subhead, "");
+        assert_eq!(postponed.warnings.len(), 1);
+        assert_eq!(postponed.away.abbreviation, "New York Mets");
+        assert_eq!(postponed.home.abbreviation, "Yankees");
+        assert_eq!(postponed.home.score, None);
+        let unwritten = game(&format!(
+            r#"{{{}, "content": {{"editorial": {{"recap": {{}}}}}}}}"#,
+            teams
+        ));
+        assert_eq!(unwritten.headline, "New York Mets at New York Yankees");
+        let photoless = game(&format!(
+            r#"{{{}, "content": {{"editorial": {{"recap": {{"home": {{"headline": "Rained out"}}}}}}}}}}"#,
+            teams
+        ));
+        assert_eq!(photoless.headline, "Rained out");
+        assert_eq!(
+            photoless.warnings,
+            vec![
+                "The recap has no 480x270 photo",
+                "The recap has no 320x180 photo"
+            ]
+        );
+        assert!(photoless.large.is_none() && photoless.small.is_none());
+        // A recap that's missing bits and pieces still makes it into the lineup.
+        let sketchy = game(&format!(
+            r#"{{{}, "content": {{"editorial": {{"recap": {{"home": {{
+                "photo": {{"cuts": {{"480x270": {{"width": 480}}}}}}
+            }}}}}}}}}}"#,
+            teams
+        ));
+        assert_eq!(sketchy.headline, "New York Mets at New York Yankees");
+        assert_eq!(
+            sketchy.warnings,
+            vec![
+                "The recap has no 480x270 photo",
+                "The recap has no 320x180 photo",
+                "The recap has no headline"
+            ]
+        );
+        let uncut = game(&format!(
+            r#"{{{}, "content": {{"editorial": {{"recap": {{"home": {{"headline": "Rained out", "photo": {{}}}}}}}}}}}}"#,
+            teams
+        ));
+        assert_eq!(uncut.headline, "Rained out");
+        assert_eq!(uncut.warnings.len(), 2);
+    }
+
+    #[test]
+    fn game_states() {
+        let start: DateTime<Utc> = "2018-06-10T23:05:00Z".parse().unwrap();
+        let status = |abstract_game_state: &str, detailed_state: &str| api::Status {
+            abstract_game_state: abstract_game_state.to_string(),
+            detailed_state: detailed_state.to_string(),
+            reason: None,
+        };
+        let linescore = api::Linescore {
+            current_inning: Some(7),
+            current_inning_ordinal: Some("7th".to_string()),
+            inning_state: Some("Top".to_string()),
+            scheduled_innings: Some(9),
+            innings: vec![],
+            teams: None,
+        };
+        assert_eq!(
+            State::new(&status("Preview", "Scheduled"), None, start),
+            State::Scheduled(start)
+        );
+        assert_eq!(
+            State::new(&status("Live", "In Progress"), Some(&linescore), start).to_string(),
+            "Top 7th"
+        );
+        assert_eq!(
+            State::new(&status("Live", "In Progress"), None, start).to_string(),
+            "In Progress"
+        );
+        assert_eq!(
+            State::new(&status("Final", "Game Over"), Some(&linescore), start),
+            State::Final
+        );
+        assert_eq!(
+            State::new(&status("Live", "Delayed"), None, start).to_string(),
+            "Delayed"
+        );
+        let scheduled = State::Scheduled(start);
+        let team = |abbreviation: &str| Team {
+            name: String::new(),
+            abbreviation: abbreviation.to_string(),
+            score: None,
+            record: None,
+            winner: false,
+        };
+        let scoreboard = Scoreboard {
+            away: &team("NYM"),
+            home: &team("NYY"),
+            state: &scheduled,
+        };
+        assert_eq!(
+            scoreboard.to_string(),
+            format!(
+                "NYM  @  NYY   {}",
+                start.with_timezone(&Local).format("%-I:%M %p")
+            )
+        );
+    }
+
+    /// A schedule that never arrives, which flags when whoever was waiting on it gives up.
+    struct Hang(Arc<std::sync::atomic::AtomicBool>);
+
+    struct Abandoned(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Drop for Abandoned {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl api::ScheduleSource for Hang {
+        fn fetch(&self, _: &api::ScheduleQuery) -> api::Fetch {
+            let abandoned = Abandoned(self.0.clone());
+            Box::pin(async move {
+                let _abandoned = abandoned;
+                tokio::time::delay_for(Duration::from_secs(3600)).await;
+                unreachable!()
+            })
+        }
+
+        fn feed(&self, _: u64) -> api::Fetch<api::Feed> {
+            unreachable!()
+        }
+
+        fn linescore(&self, _: u64) -> api::Fetch<api::Linescore> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn dropping_a_loader_cancels_the_fetch() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let abandoned = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let sources = Sources {
+                schedule: Arc::new(Hang(abandoned.clone())),
+                photos: PhotoSource::Directory(PathBuf::from("assets")),
+            };
+            let date = NaiveDate::from_ymd_opt(2018, 6, 10).unwrap();
+            let mut loader = Loader::new(&sources, api::ScheduleQuery::new(date));
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+            assert!(loader.poll().is_none());
+            assert!(!abandoned.load(Ordering::SeqCst));
+            drop(loader);
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+            assert!(abandoned.load(Ordering::SeqCst));
+        });
+    }
+
+    #[test]
+    fn groups_games_by_day() {
+        let game = |pk: u64| {
+            format!(
+                r#"{{"gamePk": {}, "gameDate": "2018-07-17T23:05:00Z",
+                    "status": {{"abstractGameState": "Preview", "detailedState": "Scheduled"}},
+                    "teams": {{
+                        "away": {{"team": {{"id": 121, "name": "New York Mets"}}}},
+                        "home": {{"team": {{"id": 147, "name": "New York Yankees"}}}}
+                    }}}}"#,
+                pk
+            )
+        };
+        let schedule = |dates: &[(&str, Vec<u64>)]| {
+            let dates: Vec<String> = dates
+                .iter()
+                .map(|(date, games)| {
+                    let games: Vec<String> = games.iter().map(|pk| game(*pk)).collect();
+                    format!(r#"{{"date": "{}", "games": [{}]}}"#, date, games.join(","))
+                })
+                .collect();
+            Schedule::new(
+                serde_json::from_str(&format!(
+                    r#"{{"copyright": "", "dates": [{}]}}"#,
+                    dates.join(",")
+                ))
+                .unwrap(),
+                &PhotoSource::Directory(PathBuf::from("assets")),
+            )
+        };
+        // The All-Star break, and an off day for everyone.
+        let mut empty = schedule(&[("2018-07-16", vec![])]);
+        assert!(empty.games.is_empty());
+        assert!(empty.focused().is_none());
+        empty.left();
+        empty.right();
+        assert!(schedule(&[]).games.is_empty());
+
+        let date = |day| NaiveDate::from_ymd_opt(2018, 7, day).unwrap();
+        let mut schedule = schedule(&[
+            ("2018-07-16", vec![]),
+            ("2018-07-17", vec![1, 2]),
+            ("2018-07-18", vec![3, 4]),
+        ]);
+        let days: Vec<(NaiveDate, Vec<u64>)> = schedule
+            .days()
+            .into_iter()
+            .map(|(date, games)| (date, games.iter().map(|game| game.id).collect()))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                (date(16), vec![]),
+                (date(17), vec![1, 2]),
+                (date(18), vec![3, 4])
+            ]
+        );
+        let headers: Vec<Option<NaiveDate>> = schedule.page().iter().map(Snippet::day).collect();
+        assert_eq!(headers, [Some(date(17)), None, Some(date(18)), None]);
+    }
+
+    #[test]
+    fn refreshes_without_losing_place() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            // The first game is still under way as far as the lineup knows, whereas the
+            // schedule that the refresh gets back has it as final.
+            let mut live: serde_json::Value =
+                serde_json::from_slice(api::testing::TEST_DATA).unwrap();
+            live["dates"][0]["games"][0]["status"] = serde_json::json!({
+                "abstractGameState": "Live",
+                "codedGameState": "I",
+                "detailedState": "In Progress"
+            });
+            // The photo of the second game gets swapped out for another one by the refresh.
+            live["dates"][0]["games"][1]["content"]["editorial"]["recap"]["home"]["photo"]
+                ["cuts"]["480x270"]["src"] = serde_json::json!("https://example.com/old.jpg");
+            let sources = Sources {
+                schedule: Arc::new(api::testing::FixtureSource::new(api::testing::TEST_DATA)),
+                photos: PhotoSource::Directory(PathBuf::from("assets")),
+            };
+            let lineup = || {
+                Schedule::new(
+                    serde_json::from_value(live.clone()).unwrap(),
+                    &sources.photos,
+                )
+            };
+            let settings = RefreshSettings {
+                interval: Duration::from_secs(3600),
+                live_interval: Duration::from_millis(0),
+            };
+            let query = api::ScheduleQuery::new(NaiveDate::from_ymd_opt(2018, 6, 10).unwrap());
+            // Days gone by are left alone, no matter what state their games are in.
+            let mut schedule = lineup();
+            let mut refresher = Refresher {
+                today: || NaiveDate::from_ymd_opt(2018, 6, 11).unwrap(),
+                ..Refresher::new(query.clone(), settings)
+            };
+            for _ in 0..5 {
+                refresher.poll(&sources, &mut schedule);
+                tokio::time::delay_for(Duration::from_millis(10)).await;
+            }
+            assert!(refresher.fetch.is_none());
+            assert!(matches!(schedule.games[0].state, State::InProgress(_)));
+            // Whereas today's lineup is kept up to date.
+            let mut schedule = lineup();
+            schedule.right();
+            schedule.right();
+            let photo = schedule.games[2].large.as_ref().unwrap().id;
+            let replaced = schedule.games[1].large.as_ref().unwrap().key();
+            assert_eq!(
+                schedule.refresh_interval(&settings),
+                Some(settings.live_interval)
+            );
+            let mut refresher = Refresher {
+                today: || NaiveDate::from_ymd_opt(2018, 6, 10).unwrap(),
+                ..Refresher::new(query, settings)
+            };
+            let mut dropped = vec![];
+            while schedule.games[0].state != State::Final {
+                dropped.extend(refresher.poll(&sources, &mut schedule));
+                tokio::time::delay_for(Duration::from_millis(10)).await;
+            }
+            assert_eq!(dropped, vec![replaced]);
+            assert_ne!(schedule.games[1].large.as_ref().unwrap().key(), replaced);
+            assert_eq!(schedule.games.len(), 7);
+            assert_eq!(schedule.focused().unwrap().id, 530783);
+            assert_eq!(schedule.games[2].large.as_ref().unwrap().id, photo);
+            // The postponed game could yet be rescheduled for later in the day, so today is
+            // still worth a look every now and then, just not as often.
+            assert_eq!(
+                schedule.refresh_interval(&settings),
+                Some(settings.interval)
+            );
+            schedule.games.pop();
+            assert_eq!(schedule.refresh_interval(&settings), None);
+        });
+    }
+
+    #[test]
+    fn backoff_doubles_until_capped() {
+        let delays: Vec<u64> = (1..=8).map(|attempt| backoff(attempt).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(backoff(0).as_secs(), 1);
+        assert_eq!(backoff(u32::MAX).as_secs(), 60);
+    }
+}
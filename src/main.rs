@@ -1,41 +1,239 @@
 #[macro_use]
 extern crate lazy_static;
 
-use image::{ImageFormat, RgbaImage};
-use piston_window::{EventLoop, Glyphs, ReleaseEvent, Transformed};
+use chrono::NaiveDate;
+use image::RgbaImage;
+use piston_window::{
+    EventLoop, FocusEvent, Glyphs, RenderEvent, ResizeEvent, Transformed, UpdateEvent, Window,
+};
+use std::path::PathBuf;
 use std::process::exit;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+mod animation;
 mod api;
+mod client;
+mod detail;
+mod images;
+mod input;
+mod layout;
 mod lineup;
+mod paging;
+mod repeat;
+mod task;
+mod text;
+mod textures;
 
+use detail::Detail;
+use input::{Action, Signal};
+use layout::{Layout, TextArea, Tile};
 use lineup::*;
+use text::TextBox;
+use textures::TextureCache;
 
 static BACKGROUND_BYTES: &[u8] = include_bytes!("../assets/background.jpg");
 
 static LEFT_ARROW_BYTES: &[u8] = include_bytes!("../assets/left_arrow.png");
 static RIGHT_ARROW_BYTES: &[u8] = include_bytes!("../assets/right_arrow.png");
 static FONT: &[u8] = include_bytes!("../OpenSans-Bold.ttf");
+static BACKGROUND_KEY: ImageKey = ImageKey::Asset("background");
+static LEFT_ARROW_KEY: ImageKey = ImageKey::Asset("left_arrow");
+static RIGHT_ARROW_KEY: ImageKey = ImageKey::Asset("right_arrow");
+static UNAVAILABLE: &str = "Image unavailable";
 static BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+static SHADE: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
 static WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
-static PADDING: f64 = 27.5;
+/// The frame rate to sit at normally, and the one to bump it up to while the carousel is moving.
+static IDLE_FPS: u64 = 10;
+static ANIMATION_FPS: u64 = 60;
 
 lazy_static! {
-    static ref BACKGROUND: RgbaImage =
-        image::load_from_memory_with_format(BACKGROUND_BYTES, ImageFormat::JPEG)
-            .unwrap()
-            .into_rgba();
-    static ref LEFT_ARROW: RgbaImage =
-        image::load_from_memory_with_format(LEFT_ARROW_BYTES, ImageFormat::PNG)
-            .unwrap()
-            .into_rgba();
-    static ref RIGHT_ARROW: RgbaImage =
-        image::load_from_memory_with_format(RIGHT_ARROW_BYTES, ImageFormat::PNG)
-            .unwrap()
-            .into_rgba();
+    static ref BACKGROUND: RgbaImage = images::decode(None, BACKGROUND_BYTES).unwrap();
+    static ref LEFT_ARROW: RgbaImage = images::decode(None, LEFT_ARROW_BYTES).unwrap();
+    static ref RIGHT_ARROW: RgbaImage = images::decode(None, RIGHT_ARROW_BYTES).unwrap();
+}
+
+static USAGE: &str = "Usage: DDS [YYYY-MM-DD] [--team <teamId>] [--sport <sportId>] \
+    [--days <count>] [--schedule-file <path>] [--photo-dir <path>] [--refresh <seconds>] \
+    [--live-refresh <seconds>] [--page-size <count>] [--scroll <page|item>] [--wraparound] \
+    [--animation <milliseconds>] [--easing <linear|ease-out|ease-in-out>] \
+    [--repeat-delay <milliseconds>] [--repeat-interval <milliseconds>] [--bindings <path>]";
+
+/// Everything that can be configured from the command line.
+struct Args {
+    query: api::ScheduleQuery,
+    sources: Sources,
+    refresh: RefreshSettings,
+    paging: paging::Settings,
+    animation: animation::Settings,
+    repeat: repeat::Settings,
+    bindings: input::Bindings,
+}
+
+/// Parses the command line. The date is an optional positional argument formatted as
+/// YYYY-MM-DD which defaults to today's date in the local timezone. Passing --days lines up
+/// that many days' worth of games, starting from the date.
+///
+/// Passing a --schedule-file runs the application against a schedule that was saved to disk
+/// rather than statsapi. Only the days in it that the date (and --days) cover are shown, so a
+/// saved schedule wants its date passing too. A --photo-dir serves the photos up out of a local
+/// directory (which defaults to the directory that the schedule file is in). Together they make
+/// for a fully offline mode. The feed and linescore behind each game's detail view are read from a
+/// feed_<gamePk>.json and linescore_<gamePk>.json alongside the schedule file.
+///
+/// A lineup that takes in today is refreshed in the background every --refresh seconds while
+/// there are games still to come, and every --live-refresh seconds while any of them are under
+/// way.
+///
+/// The carousel shows --page-size games at a time, and either flips over a whole page at a time
+/// or slides along a game at a time (--scroll page or --scroll item). With --wraparound, going
+/// right from the last game comes back around to the first. The tiles take --animation
+/// milliseconds to slide into place, following the --easing curve. Zero turns that off.
+///
+/// Holding Left or Right starts repeating after --repeat-delay milliseconds, at first every
+/// --repeat-interval milliseconds and then quicker and quicker.
+///
+/// The keyboard, mouse wheel and gamepad controls can all be remapped with a --bindings file.
+/// See input::Bindings::from_toml for what goes in it. Gamepads are only read when built with
+/// the gamepad feature (which the windows target in the Makefile does).
+fn parse_args() -> Args {
+    let mut date = chrono::Local::now().date_naive();
+    let mut team = None;
+    let mut sport = None;
+    let mut days: u32 = 1;
+    let mut schedule_file: Option<PathBuf> = None;
+    let mut photo_dir: Option<PathBuf> = None;
+    let mut refresh = RefreshSettings::default();
+    let mut paging = paging::Settings::default();
+    let mut animation = animation::Settings::default();
+    let mut repeat = repeat::Settings::default();
+    let mut bindings = input::Bindings::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--team" => team = Some(parse_arg(&arg, args.next())),
+            "--sport" => sport = Some(parse_arg(&arg, args.next())),
+            "--days" => days = parse_arg(&arg, args.next()),
+            "--schedule-file" => schedule_file = Some(parse_arg(&arg, args.next())),
+            "--photo-dir" => photo_dir = Some(parse_arg(&arg, args.next())),
+            "--refresh" => refresh.interval = Duration::from_secs(parse_arg(&arg, args.next())),
+            "--page-size" => paging.page_size = parse_arg(&arg, args.next()),
+            "--scroll" => paging.scroll = parse_arg(&arg, args.next()),
+            "--wraparound" => paging.wraparound = true,
+            "--animation" => {
+                animation.duration = Duration::from_millis(parse_arg(&arg, args.next()))
+            }
+            "--easing" => animation.easing = parse_arg(&arg, args.next()),
+            "--repeat-delay" => repeat.delay = Duration::from_millis(parse_arg(&arg, args.next())),
+            "--repeat-interval" => {
+                repeat.interval = Duration::from_millis(parse_arg(&arg, args.next()))
+            }
+            "--bindings" => {
+                let path: PathBuf = parse_arg(&arg, args.next());
+                bindings = input::Bindings::load(path).unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    exit(2);
+                })
+            }
+            "--live-refresh" => {
+                refresh.live_interval = Duration::from_secs(parse_arg(&arg, args.next()))
+            }
+            _ => {
+                date = NaiveDate::parse_from_str(&arg, "%Y-%m-%d").unwrap_or_else(|err| {
+                    eprintln!("Invalid date {:?} ({}). {}", arg, err, USAGE);
+                    exit(2);
+                })
+            }
+        }
+    }
+    let end = date
+        .checked_add_signed(chrono::Duration::days(i64::from(days.max(1)) - 1))
+        .unwrap_or_else(|| {
+            eprintln!(
+                "--days {} runs past the end of the calendar. {}",
+                days, USAGE
+            );
+            exit(2);
+        });
+    let mut query = api::ScheduleQuery::new(date).through(end);
+    if let Some(team) = team {
+        query = query.team(team);
+    }
+    if let Some(sport) = sport {
+        query = query.sport(sport);
+    }
+    let photo_dir = photo_dir.or_else(|| {
+        schedule_file
+            .as_ref()
+            .map(|file| file.parent().map(PathBuf::from).unwrap_or_default())
+    });
+    // The schedule and the photos share the one client (and so its pool of connections).
+    let client = client::Client::new(&client::Settings::default());
+    Args {
+        query,
+        sources: Sources {
+            schedule: match schedule_file {
+                Some(file) => Arc::new(api::FileSource::new(file)),
+                None => Arc::new(api::HttpSource::new(client.clone())),
+            },
+            photos: match photo_dir {
+                Some(dir) => PhotoSource::Directory(dir),
+                None => PhotoSource::Http(client),
+            },
+        },
+        refresh,
+        paging,
+        animation,
+        repeat,
+        bindings,
+    }
+}
+
+fn parse_arg<T>(flag: &str, value: Option<String>) -> T
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = value.unwrap_or_else(|| {
+        eprintln!("{} is missing its value. {}", flag, USAGE);
+        exit(2);
+    });
+    value.parse().unwrap_or_else(|err| {
+        eprintln!("Invalid {} {:?} ({}). {}", flag, value, err, USAGE);
+        exit(2);
+    })
+}
+
+/// What the window is currently showing.
+enum Screen {
+    /// A schedule is on its way in.
+    Loading(Box<Loader>),
+    /// The lineup of games for the current day, along with the detail view of one of them if
+    /// the user has opened it up. The lineup is kept around (and kept up to date) underneath so
+    /// that closing the detail view puts the user right back where they were.
+    Lineup(Schedule, Refresher, Option<Box<Detail>>),
+    /// The schedule could not be fetched. Another attempt is made at the given instant.
+    Failed(api::APIError, Instant),
+}
+
+impl Screen {
+    fn loading(sources: &Sources, query: &api::ScheduleQuery) -> Screen {
+        Screen::Loading(Box::new(Loader::new(sources, query.clone())))
+    }
 }
 
 #[tokio::main]
 async fn main() {
+    let Args {
+        mut query,
+        sources,
+        refresh,
+        paging,
+        animation,
+        repeat,
+        bindings,
+    } = parse_args();
     // Well, I know the name of the org I'm interviewing with. So I've got that going for me.
     let title = "Disney Streaming Services";
     // I chose piston simply because my quick experimentation with other libraries, such as glium,
@@ -49,7 +247,9 @@ async fn main() {
     // the shelf so...sorry, my use of it is rather blunt.
     let mut window: piston_window::PistonWindow =
         piston_window::WindowSettings::new(title, [1920, 1080])
-            .exit_on_esc(true)
+            // Back (Escape, out of the box) backs out of the detail view, so quitting is handled
+            // down in the loop.
+            .exit_on_esc(false)
             .build()
             .unwrap_or_else(|e| panic!("Failed to build PistonWindow: {}", e));
     // We're going to be using this context repeatedly in each loop.
@@ -58,17 +258,12 @@ async fn main() {
     // this library was purely a panic to find any reasonable 2D graphics library that could see
     // me through this ordeal. So I admit that this is a case of satisfying the API without
     // any real deep understanding of what they are asking of me here.
-    let mut ctx = piston_window::TextureContext {
+    //
+    // Every image goes through the texture cache so that it only gets uploaded to the GPU once.
+    let mut textures = TextureCache::new(piston_window::TextureContext {
         factory: window.factory.clone(),
         encoder: window.factory.create_command_buffer().into(),
-    };
-    let fullscreen = graphics::image::Image::new().rect([0.0, 0.0, 1920.0, 1080.0]);
-    let background: piston_window::G2dTexture = piston_window::Texture::from_image(
-        &mut ctx,
-        &(*BACKGROUND),
-        &piston_window::TextureSettings::new(),
-    )
-    .unwrap();
+    });
     // This is me TRYING to make this a bit more efficient. The downside of using this easy 2D
     // library is that I have apparently inherited a rather inefficient event loop
     // (see https://github.com/PistonDevelopers/piston/issues/1109). Frankly, I should NOT be
@@ -77,28 +272,13 @@ async fn main() {
     //
     // However, limiting the frame rate cuts the CPU usage (on my box) down to under 1% at least.
     // This framerate seemed like a fair emulation of how quickly these sorts of menus tend
-    // to render on actual TVs.
-    window.set_max_fps(10);
-    // It's kind of a useless thing to .await immediately upon application startup as it is
-    // blocking the window from rendering. I stretched for having the photos load
-    // asynchronously, however getting that initial API call to load in the background as well
-    // would have been a bit much for such a short time frame. Backlog candidate.
-    let mut schedule: Schedule = match api::Schedule::try_from(api::DEFAULT).await {
-        Ok(schedule) => schedule.into(),
-        // I handle the error of not being able to pull the initial API call and render
-        // as the sole text onto the screen. A restart is required to try again. I admit
-        // that after this, any Result given back by the graphics library I just unwrap. This
-        // is because after this point everything is already in memory so we're not suffering
-        // from IO failures, however it is entirely possible that we were given back, say,
-        // images that don't parse out correctly. I simply did not have the time to scope
-        // out such rich error handling and how that would tie into the main window rendering.
-        //
-        // I am aware that the text needs to be wrapped around as the error messages fall
-        // off the screen. Wrapping text into columns is not difficult, however you have
-        // to handle the newlines manually within this text renderer which I did not have
-        // the time to do. Some of the snippet subheaders suffer from this same problem.
-        Err(err) => display_err(err, window, background),
-    };
+    // to render on actual TVs. The carousel gets a higher frame rate while it's moving, though,
+    // as nothing looks smooth at 10 frames a second.
+    window.set_max_fps(IDLE_FPS);
+    let mut fps = IDLE_FPS;
+    // The initial schedule is fetched in the background just like every other day so that the
+    // window can start rendering (and show a loading screen) right away.
+    let mut screen = Screen::loading(&sources, &query);
     // Glyphs are the font cache that we will be using for this application.
     //
     // It's a shame, I found a cool open source font that looked very much like that blocky
@@ -113,102 +293,384 @@ async fn main() {
         piston_window::TextureSettings::new(),
     )
     .unwrap();
+    // The number of schedule fetches in a row that have failed, which drives the backoff in
+    // between automatic retries.
+    let mut failures = 0;
+    // Everything is positioned relative to the current size of the window, so the layout gets
+    // recomputed whenever the window is resized.
+    let size = window.size();
+    let mut layout = Layout::new([size.width, size.height]);
+    // Where each tile of the carousel is on screen, as they slide and grow their way to wherever
+    // the layout wants them. Tiles that come onto the page slide in from the direction that the
+    // user is heading in (1 for right, -1 for left) unless the whole lineup has only just shown
+    // up, in which case everything starts out where it belongs.
+    let mut carousel: animation::Tweens<u64, graphics::types::Rectangle> =
+        animation::Tweens::new(animation);
+    let mut direction = 1.0;
+    let mut snap = true;
+    // Left and Right move the cursor as soon as they're pressed, and then keep on moving it
+    // (faster and faster) for as long as they're held down.
+    let mut repeater = repeat::Repeater::new(repeat);
+    // The hints on screen name whichever keys are bound to what they're hinting at.
+    let another_day = hint(
+        &bindings,
+        &[Action::PrevDay, Action::NextDay],
+        "look at another day",
+    );
+    let retry_now = hint(&bindings, &[Action::Retry, Action::Select], "retry now");
+    let mut controls = input::Controls::new(bindings);
     while let Some(e) = window.next() {
-        // Move the cursor on key-up events. I would kinda like to implement fast scrolling
-        // via long key holds. But alas, into the backlog it goes.
-        match e.release_args() {
-            Some(piston_window::Button::Keyboard(piston_window::Key::Left)) => {
-                schedule.left();
+        if let Some(args) = e.resize_args() {
+            layout = Layout::new(args.window_size);
+            carousel.clear();
+            snap = true;
+        }
+        // Whatever was held down when the window lost focus gets let go of somewhere we'll never
+        // hear about, so it'd otherwise keep on repeating.
+        if e.focus_args() == Some(false) {
+            repeater.release_all();
+        }
+        // Everything other than moving the cursor happens when its button comes back up (or
+        // when the wheel clicks over).
+        let mut steps = vec![];
+        let mut actions = vec![];
+        for signal in controls.signals(&e) {
+            match signal {
+                Signal::Press(action @ (Action::Left | Action::Right)) => {
+                    if repeater.press(action) {
+                        steps.push(action);
+                    }
+                }
+                Signal::Press(_) => (),
+                Signal::Release(action) => {
+                    repeater.release(action);
+                    if action != Action::Left && action != Action::Right {
+                        actions.push(action);
+                    }
+                }
+                Signal::Tap(action @ (Action::Left | Action::Right)) => steps.push(action),
+                Signal::Tap(action) => actions.push(action),
+            }
+        }
+        if let Some(args) = e.update_args() {
+            carousel.update(args.dt);
+            if let Some((action, repeats)) = repeater.update(args.dt) {
+                steps.extend((0..repeats).map(|_| action));
+            }
+        }
+        if let Screen::Lineup(schedule, _, None) = &mut screen {
+            for action in steps {
+                if action == Action::Left {
+                    schedule.left();
+                    direction = -1.0;
+                } else {
+                    schedule.right();
+                    direction = 1.0;
+                }
+            }
+        }
+        let wanted = if carousel.animating() {
+            ANIMATION_FPS
+        } else {
+            IDLE_FPS
+        };
+        if wanted != fps {
+            fps = wanted;
+            window.set_max_fps(fps);
+        }
+        match &mut screen {
+            Screen::Loading(loader) => match loader.poll() {
+                Some(Ok(schedule)) => {
+                    failures = 0;
+                    textures.forget_photos();
+                    let refresher = Refresher::new(loader.query().clone(), refresh);
+                    screen = Screen::Lineup(schedule.with_paging(paging), refresher, None);
+                    carousel.clear();
+                    snap = true;
+                }
+                // If we can't pull a schedule then the error is rendered as the sole text onto
+                // the screen and we try again on our own after a backoff (or sooner, if the user
+                // asks for it). I admit that after this, any Result given back by the graphics
+                // library I just unwrap. This is because after this point everything is already
+                // in memory so we're not suffering from IO failures, however it is entirely
+                // possible that we were given back, say, images that don't parse out correctly.
+                Some(Err(err)) => {
+                    failures += 1;
+                    screen = Screen::Failed(err, Instant::now() + lineup::backoff(failures));
+                }
+                None => (),
+            },
+            Screen::Lineup(schedule, refresher, _) => {
+                // Photos that a refresh swaps out are never drawn again, so there's no sense in
+                // keeping their textures around on the GPU.
+                for key in refresher.poll(&sources, schedule) {
+                    textures.forget(key);
+                }
             }
-            Some(piston_window::Button::Keyboard(piston_window::Key::Right)) => {
-                schedule.right();
+            Screen::Failed(_, retry_at) if Instant::now() >= *retry_at => {
+                screen = Screen::loading(&sources, &query);
             }
             _ => (),
+        }
+        for action in actions {
+            match action {
+                // Select opens up the focused game. Its detail view is dropped (and the fetch of
+                // its feed with it) as soon as the user backs out again.
+                Action::Select => match &mut screen {
+                    Screen::Lineup(schedule, _, detail @ None) => {
+                        *detail = schedule
+                            .focused()
+                            .map(|game| Box::new(Detail::new(&sources, game)));
+                    }
+                    Screen::Failed(..) => screen = Screen::loading(&sources, &query),
+                    _ => (),
+                },
+                Action::Retry => {
+                    if let Screen::Failed(..) = screen {
+                        screen = Screen::loading(&sources, &query);
+                    }
+                }
+                Action::Back => match &mut screen {
+                    Screen::Lineup(_, _, detail @ Some(_)) => *detail = None,
+                    _ => window.set_should_close(true),
+                },
+                // NextDay and PrevDay flip through the days. Mashing them simply replaces
+                // whatever request was already in flight.
+                Action::PrevDay => {
+                    query = query.previous_day();
+                    failures = 0;
+                    screen = Screen::loading(&sources, &query);
+                }
+                Action::NextDay => {
+                    query = query.next_day();
+                    failures = 0;
+                    screen = Screen::loading(&sources, &query);
+                }
+                Action::Left | Action::Right => (),
+            }
+        }
+        let schedule = match &mut screen {
+            Screen::Lineup(_, _, Some(detail)) => {
+                detail.poll();
+                draw_detail(&mut window, &e, &layout, &mut textures, &mut glyphs, detail);
+                continue;
+            }
+            // Off days and the All-Star break still deserve a screen of their own, rather than
+            // an empty carousel.
+            Screen::Lineup(schedule, _, None) if schedule.games.is_empty() => {
+                let mut lines = vec![format!("No games scheduled for {}.", days(&query))];
+                lines.extend(another_day.clone());
+                draw_notice(
+                    &mut window,
+                    &e,
+                    layout.notice(),
+                    &layout,
+                    &mut textures,
+                    &mut glyphs,
+                    &lines,
+                );
+                continue;
+            }
+            Screen::Lineup(schedule, _, None) => schedule,
+            Screen::Loading(loader) => {
+                let loading = format!("Loading games for {}\u{2026}", days(loader.query()));
+                draw_notice(
+                    &mut window,
+                    &e,
+                    layout.notice(),
+                    &layout,
+                    &mut textures,
+                    &mut glyphs,
+                    &[loading],
+                );
+                continue;
+            }
+            Screen::Failed(err, retry_at) => {
+                let countdown = retry_at.saturating_duration_since(Instant::now());
+                let retrying = format!("Retrying in {} seconds.", countdown.as_secs() + 1);
+                let lines = [
+                    format!("{}", err),
+                    match &retry_now {
+                        Some(retry_now) => format!("{} {}", retrying, retry_now),
+                        None => retrying,
+                    },
+                ];
+                draw_notice(
+                    &mut window,
+                    &e,
+                    layout.notice(),
+                    &layout,
+                    &mut textures,
+                    &mut glyphs,
+                    &lines,
+                );
+                continue;
+            }
         };
         window.draw_2d(&e, |c, g, device| {
             // This is the main rendering loop as per piston convention.
             //
-            // I admit that these X/Y transformations are more of a result
-            // of me experimenting around to get an orientation on the page
-            // and seeing what works aesthetically. I did do some manual computations
-            // to get an idea of where these objects should lay on the screen.
-            // However, by and large, I am admitting that this applications is not
-            // "responsive" in the sense that it does not respond to different sizes.
-            // In Agile terms, I reckon that I would put that work onto the next sprint.
+            // Where everything goes is worked out by the layout against the current size of the
+            // window, so all that's left to do here is to put the pictures in their frames.
             piston_window::clear(BLACK, g);
-            fullscreen.draw(&background, &graphics::DrawState::default(), c.transform, g);
-            // The first item is padded from the left most wall of the screen.
-            let mut left_edge = PADDING;
-            // And the right edge is computed as the left_edge plus
-            // whatever the width of the image is.
-            let mut right_edge: f64;
-            for item in schedule.page() {
-                match item {
-                    Snippet::Large(image, heading, subheading) => {
-                        right_edge = left_edge + image.width() as f64;
-                        let rect = graphics::image::Image::new().rect([
-                            0.0,
-                            0.0,
-                            image.width() as f64,
-                            image.height() as f64,
-                        ]);
-                        let txt = piston_window::Texture::from_image(
-                            &mut ctx,
-                            image,
-                            &piston_window::TextureSettings::new(),
-                        )
-                        .unwrap();
-                        rect.draw(
-                            &txt,
-                            &graphics::DrawState::default(),
-                            c.transform.trans(left_edge, 540.0),
-                            g,
-                        );
-                        // Render our header and subheader
-                        piston_window::text(
+            graphics::image::Image::new()
+                .rect(layout.fullscreen())
+                .draw(
+                    textures.get(BACKGROUND_KEY, &BACKGROUND),
+                    &graphics::DrawState::default(),
+                    c.transform,
+                    g,
+                );
+            let page = schedule.page();
+            let tiles = layout.tiles(
+                &page
+                    .iter()
+                    .map(|item| match item {
+                        Snippet::Large { .. } => Tile::Large,
+                        Snippet::Small { .. } => Tile::Small,
+                    })
+                    .collect::<Vec<Tile>>(),
+            );
+            let ids: Vec<u64> = page.iter().map(Snippet::id).collect();
+            carousel.retain(&ids);
+            let mut previous = None;
+            for (item, target) in page.into_iter().zip(tiles) {
+                let tile = carousel.animate(item.id(), target, || {
+                    if snap {
+                        target
+                    } else {
+                        [
+                            target[0] + direction * target[2],
+                            target[1],
+                            target[2],
+                            target[3],
+                        ]
+                    }
+                });
+                // Each day gets a header, and every day after the first on the page gets a line
+                // to set it apart from the day before.
+                if let Some(day) = item.day() {
+                    if let Some(previous) = previous {
+                        let separator = layout.separator(previous, tile);
+                        graphics::rectangle(WHITE, separator, c.transform, g);
+                    }
+                    let area = layout.day(tile);
+                    TextBox::new(layout.font_size(), area.width)
+                        .max_lines(1)
+                        .draw(
                             WHITE,
-                            16,
-                            heading,
+                            &day.format("%A, %B %-d").to_string(),
                             &mut glyphs,
-                            c.transform.trans(left_edge + 40.0, 500.0),
+                            c.transform.trans(area.x, area.y),
                             g,
                         )
                         .unwrap();
-                        piston_window::text(
-                            WHITE,
-                            16,
-                            subheading,
-                            &mut glyphs,
-                            c.transform.trans(left_edge, 855.0),
+                }
+                previous = Some(tile);
+                match item {
+                    Snippet::Large {
+                        picture,
+                        scoreboard,
+                        decisions,
+                        headline: heading,
+                        subhead: subheading,
+                        warnings,
+                        ..
+                    } => {
+                        graphics::image::Image::new().rect(tile).draw(
+                            textures.get(picture.key, picture.image),
+                            &graphics::DrawState::default(),
+                            c.transform,
                             g,
-                        )
-                        .unwrap();
+                        );
+                        draw_scoreboard(&scoreboard, tile, &layout, &mut glyphs, &c, g);
+                        // The focused tile has enough room to say why its photo is missing, along
+                        // with anything else that was missing for the game.
+                        let mut caption = warnings.to_vec();
+                        if let Some(err) = picture.failure {
+                            caption.insert(0, format!("{}\n{}", UNAVAILABLE, err));
+                        }
+                        if !caption.is_empty() {
+                            let area = layout.caption(tile);
+                            TextBox::new(layout.font_size(), area.width)
+                                .max_lines(6)
+                                .draw(
+                                    WHITE,
+                                    &caption.join("\n"),
+                                    &mut glyphs,
+                                    c.transform.trans(area.x, area.y),
+                                    g,
+                                )
+                                .unwrap();
+                        }
+                        // Render our header and subheader. The header sits on top of the
+                        // photo, so it grows upwards as it wraps rather than into the photo.
+                        let area = layout.heading(tile);
+                        let heading_box = TextBox::new(layout.font_size(), area.width).max_lines(2);
+                        let heading = heading_box.wrap(&mut glyphs, heading).unwrap();
+                        let heading_top =
+                            area.y - heading_box.line_height() * (heading.len().max(1) - 1) as f64;
+                        heading_box
+                            .draw_lines(
+                                WHITE,
+                                &heading,
+                                &mut glyphs,
+                                c.transform.trans(area.x, heading_top),
+                                g,
+                            )
+                            .unwrap();
+                        let area = layout.decisions(tile);
+                        TextBox::new(layout.font_size(), area.width)
+                            .max_lines(1)
+                            .draw(
+                                WHITE,
+                                &decisions.to_string(),
+                                &mut glyphs,
+                                c.transform.trans(area.x, area.y),
+                                g,
+                            )
+                            .unwrap();
+                        let area = layout.subheading(tile);
+                        TextBox::new(layout.font_size(), area.width)
+                            .max_lines(3)
+                            .draw(
+                                WHITE,
+                                subheading,
+                                &mut glyphs,
+                                c.transform.trans(area.x, area.y),
+                                g,
+                            )
+                            .unwrap();
                         glyphs.factory.encoder.flush(device);
                     }
-                    Snippet::Small(image) => {
-                        right_edge = left_edge + image.width() as f64;
-                        let rect = graphics::image::Image::new().rect([
-                            0.0,
-                            0.0,
-                            image.width() as f64,
-                            image.height() as f64,
-                        ]);
-                        let txt = piston_window::Texture::from_image(
-                            &mut ctx,
-                            image,
-                            &piston_window::TextureSettings::new(),
-                        )
-                        .unwrap();
-                        rect.draw(
-                            &txt,
+                    Snippet::Small {
+                        picture,
+                        scoreboard,
+                        ..
+                    } => {
+                        graphics::image::Image::new().rect(tile).draw(
+                            textures.get(picture.key, picture.image),
                             &graphics::DrawState::default(),
-                            c.transform.trans(left_edge, 578.5),
+                            c.transform,
                             g,
                         );
+                        draw_scoreboard(&scoreboard, tile, &layout, &mut glyphs, &c, g);
+                        if picture.failure.is_some() {
+                            let area = layout.caption(tile);
+                            TextBox::new(layout.font_size(), area.width)
+                                .max_lines(1)
+                                .draw(
+                                    WHITE,
+                                    UNAVAILABLE,
+                                    &mut glyphs,
+                                    c.transform.trans(area.x, area.y),
+                                    g,
+                                )
+                                .unwrap();
+                        }
+                        glyphs.factory.encoder.flush(device);
                     }
                 }
-                // This is computing the small padding inbetween snippets.
-                left_edge = right_edge + 27.5;
             }
             // has_less and has_more describe whether or not there is a page to left or the right,
             // which drives the decision on whether or not to render the scroll arrow indicators.
@@ -220,78 +682,170 @@ async fn main() {
             // eventually anyways, and their implementation buys you a bit more time (politically)
             // to implement the harder stuff while keeping everyone happy.
             if schedule.has_less() {
-                let txt = piston_window::Texture::from_image(
-                    &mut ctx,
-                    &*LEFT_ARROW,
-                    &piston_window::TextureSettings::new(),
-                )
-                .unwrap();
-                let rect = graphics::image::Image::new().rect([
-                    0.0,
-                    0.0,
-                    LEFT_ARROW.width() as f64,
-                    LEFT_ARROW.height() as f64,
-                ]);
-                rect.draw(&txt, &graphics::DrawState::default(), c.transform, g);
+                graphics::image::Image::new()
+                    .rect(layout.left_arrow())
+                    .draw(
+                        textures.get(LEFT_ARROW_KEY, &LEFT_ARROW),
+                        &graphics::DrawState::default(),
+                        c.transform,
+                        g,
+                    );
             }
             if schedule.has_more() {
-                let txt = piston_window::Texture::from_image(
-                    &mut ctx,
-                    &*RIGHT_ARROW,
-                    &piston_window::TextureSettings::new(),
-                )
-                .unwrap();
-                let rect = graphics::image::Image::new().rect([
-                    0.0,
-                    0.0,
-                    RIGHT_ARROW.width() as f64,
-                    RIGHT_ARROW.height() as f64,
-                ]);
-                rect.draw(
-                    &txt,
-                    &graphics::DrawState::default(),
-                    c.transform.trans(1920.0 - RIGHT_ARROW.width() as f64, 0.0),
-                    g,
-                );
+                graphics::image::Image::new()
+                    .rect(layout.right_arrow())
+                    .draw(
+                        textures.get(RIGHT_ARROW_KEY, &RIGHT_ARROW),
+                        &graphics::DrawState::default(),
+                        c.transform,
+                        g,
+                    );
             }
         });
+        // Once the tiles have actually been put on screen, anything that comes onto the page
+        // from here on out slides in. Other events don't draw anything.
+        if e.render_args().is_some() {
+            snap = false;
+        }
     }
 }
 
-// The error case alternative. It takes ownership of the window and displays the APIError until exit.
-fn display_err(
-    err: api::APIError,
-    mut window: piston_window::PistonWindow,
-    background: piston_window::G2dTexture,
-) -> ! {
-    let err_text = format!("{}", err);
-    let mut glyphs = Glyphs::from_bytes(
-        FONT,
-        piston_window::TextureContext {
-            factory: window.factory.clone(),
-            encoder: window.factory.create_command_buffer().into(),
-        },
-        piston_window::TextureSettings::new(),
-    )
-    .unwrap();
-    let fullscreen = graphics::image::Image::new().rect([0.0, 0.0, 1920.0, 1080.0]);
-    while let Some(e) = window.next() {
-        window.draw_2d(&e, |c, g, device| {
-            piston_window::clear(BLACK, g);
-            fullscreen.draw(&background, &graphics::DrawState::default(), c.transform, g);
-            piston_window::text(
-                WHITE,
-                16,
-                err_text.as_str(),
-                &mut glyphs,
-                c.transform.trans(0.0, 500.0),
-                g,
-            )
-            .unwrap();
-            glyphs.factory.encoder.flush(device);
-        });
+// Renders the scoreboard in a shaded strip underneath the given tile.
+fn draw_scoreboard(
+    scoreboard: &Scoreboard,
+    tile: graphics::types::Rectangle,
+    layout: &Layout,
+    glyphs: &mut Glyphs,
+    c: &graphics::Context,
+    g: &mut piston_window::G2d,
+) {
+    let strip = layout.scoreboard(tile);
+    graphics::rectangle(SHADE, strip, c.transform, g);
+    let area = layout.scoreboard_text(strip);
+    TextBox::new(layout.font_size(), area.width)
+        .max_lines(1)
+        .draw(
+            WHITE,
+            &scoreboard.to_string(),
+            glyphs,
+            c.transform.trans(area.x, area.y),
+            g,
+        )
+        .unwrap();
+}
+
+// Tells the user which keys to press to do something, E.G. "Press R or Enter to retry now.",
+// going by whatever is bound to the given actions. There's nothing to say if none of them have
+// a key.
+fn hint(bindings: &input::Bindings, actions: &[Action], what: &str) -> Option<String> {
+    let keys: Vec<String> = actions
+        .iter()
+        .filter_map(|&action| bindings.key_name(action))
+        .collect();
+    if keys.is_empty() {
+        None
+    } else {
+        Some(format!("Press {} to {}.", keys.join(" or "), what))
     }
-    exit(1);
 }
 
+// The days that a query covers, E.G. "Sunday, June 10, 2018" or, for a range,
+// "Monday, July 16, 2018 through Wednesday, July 18, 2018".
+fn days(query: &api::ScheduleQuery) -> String {
+    let format = |date: NaiveDate| date.format("%A, %B %-d, %Y").to_string();
+    if query.end_date() == query.date() {
+        format(query.date())
+    } else {
+        format!(
+            "{} through {}",
+            format(query.date()),
+            format(query.end_date())
+        )
+    }
+}
 
+// Renders the detail view of a game: its heading, then the inning-by-inning grid once the
+// linescore comes in, then everything else.
+fn draw_detail(
+    window: &mut piston_window::PistonWindow,
+    e: &piston_window::Event,
+    layout: &Layout,
+    textures: &mut TextureCache,
+    glyphs: &mut Glyphs,
+    detail: &Detail,
+) {
+    window.draw_2d(e, |c, g, device| {
+        piston_window::clear(BLACK, g);
+        graphics::image::Image::new()
+            .rect(layout.fullscreen())
+            .draw(
+                textures.get(BACKGROUND_KEY, &BACKGROUND),
+                &graphics::DrawState::default(),
+                c.transform,
+                g,
+            );
+        let area = layout.detail();
+        let paragraph = TextBox::new(layout.font_size(), area.width).max_lines(12);
+        let mut top = area.y;
+        for line in detail.heading() {
+            top += paragraph
+                .draw(WHITE, &line, glyphs, c.transform.trans(area.x, top), g)
+                .unwrap();
+        }
+        if let Some(grid) = detail.grid() {
+            // The paragraphs are positioned by their baselines, whereas the grid goes by its
+            // top left corner.
+            let corner = [area.x, top - f64::from(layout.font_size())];
+            let backdrop = layout.grid(corner, grid.rows.len(), grid.columns());
+            graphics::rectangle(SHADE, backdrop, c.transform, g);
+            let cell = TextBox::new(layout.font_size(), area.width).max_lines(1);
+            for (r, row) in grid.rows.iter().enumerate() {
+                for (column, text) in row.iter().enumerate() {
+                    let [x, y] = layout.grid_cell(backdrop, r, column);
+                    cell.draw(WHITE, text, glyphs, c.transform.trans(x, y), g)
+                        .unwrap();
+                }
+            }
+            top = backdrop[1] + backdrop[3] + paragraph.line_height();
+        }
+        for line in detail.paragraphs() {
+            top += paragraph
+                .draw(WHITE, &line, glyphs, c.transform.trans(area.x, top), g)
+                .unwrap();
+        }
+        glyphs.factory.encoder.flush(device);
+    });
+}
+
+// Renders the given paragraphs of text on their own over the background, starting from the
+// given area. This is used for anything that stands in for the lineup, such as the loading and
+// error screens.
+fn draw_notice(
+    window: &mut piston_window::PistonWindow,
+    e: &piston_window::Event,
+    area: TextArea,
+    layout: &Layout,
+    textures: &mut TextureCache,
+    glyphs: &mut Glyphs,
+    lines: &[String],
+) {
+    window.draw_2d(e, |c, g, device| {
+        piston_window::clear(BLACK, g);
+        graphics::image::Image::new()
+            .rect(layout.fullscreen())
+            .draw(
+                textures.get(BACKGROUND_KEY, &BACKGROUND),
+                &graphics::DrawState::default(),
+                c.transform,
+                g,
+            );
+        let paragraph = TextBox::new(layout.font_size(), area.width).max_lines(12);
+        let mut top = area.y;
+        for line in lines {
+            top += paragraph
+                .draw(WHITE, line, glyphs, c.transform.trans(area.x, top), g)
+                .unwrap();
+        }
+        glyphs.factory.encoder.flush(device);
+    });
+}
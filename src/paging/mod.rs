@@ -0,0 +1,302 @@
+use std::ops::Range;
+
+/// How the page follows the cursor around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scroll {
+    /// The items are cut up into fixed pages (0..5, 5..10 and so on) and moving off the edge of
+    /// one flips over to the next. This is how the lineup has always behaved.
+    Page,
+    /// The page slides along an item at a time, just far enough to keep the cursor in view.
+    Item,
+}
+
+impl std::str::FromStr for Scroll {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Scroll, String> {
+        match s {
+            "page" => Ok(Scroll::Page),
+            "item" => Ok(Scroll::Item),
+            _ => Err(format!("{:?} is neither page nor item", s)),
+        }
+    }
+}
+
+/// How the lineup gets cut up into pages, each of which has a flag of its own (--page-size,
+/// --scroll and --wraparound).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// The most items that are on screen at once. Zero is taken to mean one.
+    pub page_size: usize,
+    pub scroll: Scroll,
+    /// Whether moving past the last item comes back around to the first (and vice versa).
+    pub wraparound: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            page_size: 5,
+            scroll: Scroll::Page,
+            wraparound: false,
+        }
+    }
+}
+
+/// A cursor over a list of items, along with the window of them (the page) that is on screen.
+///
+/// The Pager never sees the items themselves, only how many there are, so it has nothing to say
+/// about anything being in or out of bounds beyond keeping the cursor and page within 0..len.
+/// An empty list has an empty page, and a cursor that sits at zero pointing at nothing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pager {
+    settings: Settings,
+    len: usize,
+    cursor: usize,
+    /// Where the page starts. Only Scroll::Item needs to remember this, as its page depends on
+    /// how the cursor got to where it is rather than just on where it is.
+    start: usize,
+}
+
+impl Pager {
+    pub fn new(settings: Settings, len: usize) -> Pager {
+        Pager {
+            settings,
+            len,
+            cursor: 0,
+            start: 0,
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn left(&mut self) {
+        if self.cursor > 0 {
+            self.focus(self.cursor - 1);
+        } else if self.settings.wraparound && self.len > 0 {
+            self.focus(self.len - 1);
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.cursor + 1 < self.len {
+            self.focus(self.cursor + 1);
+        } else if self.settings.wraparound {
+            self.focus(0);
+        }
+    }
+
+    /// Moves the cursor straight to the given item (or as close to it as there is).
+    pub fn focus(&mut self, index: usize) {
+        self.cursor = index.min(self.len.saturating_sub(1));
+        let size = self.size();
+        if self.cursor < self.start {
+            self.start = self.cursor;
+        } else if self.cursor >= self.start + size {
+            self.start = self.cursor + 1 - size;
+        }
+        // Never leave the page part empty when there are items enough to fill it.
+        self.start = self.start.min(self.len.saturating_sub(size));
+    }
+
+    /// For when the items have changed underneath the pager. The cursor stays where it was if it
+    /// still can.
+    pub fn resize(&mut self, len: usize) {
+        self.len = len;
+        self.focus(self.cursor);
+    }
+
+    /// The items that are on screen.
+    pub fn page(&self) -> Range<usize> {
+        let size = self.size();
+        let start = match self.settings.scroll {
+            Scroll::Page => self.cursor / size * size,
+            Scroll::Item => self.start,
+        };
+        start..(start + size).min(self.len)
+    }
+
+    /// Whether there are items off to the left of the page.
+    pub fn has_less(&self) -> bool {
+        self.page().start > 0
+    }
+
+    /// Whether there are items off to the right of the page.
+    pub fn has_more(&self) -> bool {
+        self.page().end < self.len
+    }
+
+    fn size(&self) -> usize {
+        self.settings.page_size.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every combination of settings worth checking.
+    fn settings() -> Vec<Settings> {
+        let mut settings = vec![];
+        for page_size in 0..=7 {
+            for &scroll in &[Scroll::Page, Scroll::Item] {
+                for &wraparound in &[false, true] {
+                    settings.push(Settings {
+                        page_size,
+                        scroll,
+                        wraparound,
+                    });
+                }
+            }
+        }
+        settings
+    }
+
+    /// The things that have to hold no matter how the pager got to where it is.
+    fn check(pager: &Pager) {
+        let page = pager.page();
+        let size = pager.settings.page_size.max(1);
+        if pager.len == 0 {
+            assert_eq!(pager.cursor(), 0);
+            assert!(page.is_empty());
+        } else {
+            assert!(pager.cursor() < pager.len, "{:?}", pager);
+            assert!(page.contains(&pager.cursor()), "{:?} {:?}", pager, page);
+        }
+        assert!(page.end <= pager.len);
+        assert!(page.len() <= size);
+        match pager.settings.scroll {
+            Scroll::Page => assert_eq!(page.start % size, 0),
+            Scroll::Item => assert_eq!(page.len(), size.min(pager.len)),
+        }
+        assert_eq!(pager.has_less(), page.start > 0);
+        assert_eq!(pager.has_more(), page.end < pager.len);
+    }
+
+    #[test]
+    fn walks_every_item() {
+        for settings in settings() {
+            for len in 0..=23 {
+                let mut pager = Pager::new(settings, len);
+                check(&pager);
+                // Going right visits every item once, in order, and the pages along the way
+                // cover every last one of them.
+                let mut visited = vec![pager.cursor()];
+                let mut seen = vec![false; len];
+                for _ in 1..len {
+                    pager.page().for_each(|index| seen[index] = true);
+                    pager.right();
+                    check(&pager);
+                    visited.push(pager.cursor());
+                }
+                pager.page().for_each(|index| seen[index] = true);
+                if len > 0 {
+                    assert_eq!(visited, (0..len).collect::<Vec<usize>>());
+                    assert!(seen.iter().all(|&seen| seen), "{:?} {}", settings, len);
+                }
+                // And then off the end.
+                pager.right();
+                check(&pager);
+                match (settings.wraparound, len) {
+                    (_, 0) => assert_eq!(pager.cursor(), 0),
+                    (true, _) => assert_eq!(pager.cursor(), 0),
+                    (false, len) => assert_eq!(pager.cursor(), len - 1),
+                }
+                // Back to the start and off the other end.
+                pager.focus(0);
+                pager.left();
+                check(&pager);
+                match (settings.wraparound, len) {
+                    (true, len) if len > 0 => assert_eq!(pager.cursor(), len - 1),
+                    _ => assert_eq!(pager.cursor(), 0),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn survives_any_sequence_of_moves() {
+        // A plain linear congruential generator, which is plenty random enough to come up with
+        // moves and doesn't need a crate.
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random = move |bound: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as usize % bound
+        };
+        for settings in settings() {
+            for len in 0..=23 {
+                let mut pager = Pager::new(settings, len);
+                for _ in 0..200 {
+                    let before = pager.cursor();
+                    match random(8) {
+                        0..=2 => {
+                            pager.left();
+                            if !settings.wraparound && before == 0 {
+                                assert_eq!(pager.cursor(), 0);
+                            }
+                        }
+                        3..=5 => pager.right(),
+                        6 => pager.focus(random(len + 3)),
+                        _ => pager.resize(random(26)),
+                    }
+                    check(&pager);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scrolling_modes() {
+        // Fourteen games with the cursor on the eighth.
+        let mut pages = Pager::new(Settings::default(), 14);
+        let mut items = Pager::new(
+            Settings {
+                scroll: Scroll::Item,
+                ..Settings::default()
+            },
+            14,
+        );
+        for _ in 0..7 {
+            pages.right();
+            items.right();
+        }
+        assert_eq!(pages.page(), 5..10);
+        assert_eq!(items.page(), 3..8);
+        // Heading back left, the sliding page doesn't move until the cursor gets to its edge.
+        for _ in 0..4 {
+            items.left();
+        }
+        assert_eq!(items.page(), 3..8);
+        items.left();
+        assert_eq!(items.page(), 2..7);
+        // The last page is whatever is left over.
+        pages.focus(13);
+        assert_eq!(pages.page(), 10..14);
+        assert!(pages.has_less() && !pages.has_more());
+    }
+
+    #[test]
+    fn resizing_keeps_the_cursor() {
+        let mut pager = Pager::new(Settings::default(), 14);
+        pager.focus(7);
+        pager.resize(20);
+        assert_eq!(pager.cursor(), 7);
+        pager.resize(4);
+        assert_eq!(pager.cursor(), 3);
+        assert_eq!(pager.page(), 0..4);
+        pager.resize(0);
+        assert_eq!(pager.cursor(), 0);
+        assert_eq!(pager.page(), 0..0);
+    }
+
+    #[test]
+    fn parses_scroll_modes() {
+        assert_eq!("page".parse(), Ok(Scroll::Page));
+        assert_eq!("item".parse(), Ok(Scroll::Item));
+        assert!("sideways".parse::<Scroll>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+/// How holding down Left or Right keeps the cursor moving, and how quickly it picks up speed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// How long a key has to be held before it starts repeating.
+    pub delay: Duration,
+    /// The time between the first couple of repeats.
+    pub interval: Duration,
+    /// Each repeat comes this much sooner than the one before it (as a fraction of the time in
+    /// between), so the longer the key is held the faster it goes.
+    pub acceleration: f64,
+    /// The fastest that repeats are allowed to get.
+    pub min_interval: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            delay: Duration::from_millis(400),
+            interval: Duration::from_millis(150),
+            acceleration: 0.85,
+            min_interval: Duration::from_millis(40),
+        }
+    }
+}
+
+/// Keeps track of which key is being held down and when it's due to repeat.
+///
+/// Only the one key repeats at a time, which is whichever was pressed last. The repeater has no
+/// clock of its own and counts down by however much time it's handed in update, which keeps it
+/// in step with the frames (and makes it easy to test).
+pub struct Repeater<K> {
+    settings: Settings,
+    held: Option<Held<K>>,
+}
+
+struct Held<K> {
+    key: K,
+    /// Seconds until the next repeat.
+    until: f64,
+    /// Seconds in between repeats, as of the next one.
+    interval: f64,
+}
+
+impl<K: Copy + PartialEq> Repeater<K> {
+    pub fn new(settings: Settings) -> Repeater<K> {
+        Repeater {
+            settings,
+            held: None,
+        }
+    }
+
+    /// Starts holding the given key. Returns whether this is a fresh press, as opposed to the
+    /// operating system's own key repeat telling us about a key we already know is held.
+    pub fn press(&mut self, key: K) -> bool {
+        if self.held.as_ref().map(|held| held.key) == Some(key) {
+            return false;
+        }
+        self.held = Some(Held {
+            key,
+            until: self.settings.delay.as_secs_f64(),
+            interval: self.settings.interval.as_secs_f64(),
+        });
+        true
+    }
+
+    pub fn release(&mut self, key: K) {
+        if self.held.as_ref().map(|held| held.key) == Some(key) {
+            self.held = None;
+        }
+    }
+
+    /// Lets go of whatever is held. For when the key-up is never going to come, such as when
+    /// the window loses focus and the key gets let go of over in some other window.
+    pub fn release_all(&mut self) {
+        self.held = None;
+    }
+
+    /// Moves time along, returning the held key along with how many times it has repeated in
+    /// the meantime (if it has at all). A slow frame can easily span more than one repeat.
+    pub fn update(&mut self, dt: f64) -> Option<(K, u32)> {
+        let settings = self.settings;
+        let held = self.held.as_mut()?;
+        held.until -= dt;
+        let fastest = settings.min_interval.as_secs_f64().max(0.001);
+        let mut repeats = 0;
+        // A hair of slack, so that frames adding up to exactly the interval count as reaching
+        // it no matter how the floating point works out.
+        while held.until <= 1e-9 {
+            repeats += 1;
+            held.until += held.interval.max(fastest);
+            held.interval = (held.interval * settings.acceleration).max(fastest);
+        }
+        Some((held.key, repeats)).filter(|(_, repeats)| *repeats > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            delay: Duration::from_millis(500),
+            interval: Duration::from_millis(200),
+            acceleration: 0.5,
+            min_interval: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn repeats_faster_and_faster() {
+        let mut repeater = Repeater::new(settings());
+        assert!(repeater.press('l'));
+        // Nothing until the delay is up.
+        assert_eq!(repeater.update(0.4), None);
+        assert_eq!(repeater.update(0.1), Some(('l', 1)));
+        // Then 200ms, 100ms, 50ms and 50ms from there on out.
+        assert_eq!(repeater.update(0.15), None);
+        assert_eq!(repeater.update(0.05), Some(('l', 1)));
+        assert_eq!(repeater.update(0.1), Some(('l', 1)));
+        assert_eq!(repeater.update(0.05), Some(('l', 1)));
+        assert_eq!(repeater.update(0.5), Some(('l', 10)));
+        repeater.release('l');
+        assert_eq!(repeater.update(10.0), None);
+    }
+
+    #[test]
+    fn one_key_at_a_time() {
+        let mut repeater = Repeater::new(settings());
+        assert!(repeater.press('l'));
+        assert_eq!(repeater.update(0.3), None);
+        // The operating system repeating the press doesn't start the delay over.
+        assert!(!repeater.press('l'));
+        assert_eq!(repeater.update(0.2), Some(('l', 1)));
+        // Another key takes over, from the top.
+        assert!(repeater.press('r'));
+        assert_eq!(repeater.update(0.4), None);
+        // Letting go of a key that isn't the one repeating changes nothing.
+        repeater.release('l');
+        assert_eq!(repeater.update(0.1), Some(('r', 1)));
+        repeater.release_all();
+        assert_eq!(repeater.update(10.0), None);
+        // And the next press starts over from scratch.
+        assert!(repeater.press('r'));
+        assert_eq!(repeater.update(0.4), None);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::api;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Work that has been spawned off onto the runtime.
+///
+/// The result is handed back over a channel which the event loop polls once per frame, so the
+/// window keeps rendering while the network does its thing. Dropping the Task cancels the work
+/// wherever it is at, which is what happens to everything that belonged to a day once the user
+/// moves on to another one.
+pub struct Task<T> {
+    channel: crossbeam_channel::Receiver<T>,
+    // Never sent on. Dropping it is what cancels the work.
+    _cancel: oneshot::Sender<()>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    /// Must be called from within a runtime.
+    pub fn spawn<F: Future<Output = T> + Send + 'static>(work: F) -> Task<T> {
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let (cancel, cancelled) = oneshot::channel::<()>();
+        tokio::task::spawn(async move {
+            tokio::select! {
+                result = work => {
+                    let _ = tx.send(result);
+                }
+                _ = cancelled => (),
+            }
+        });
+        Task {
+            channel: rx,
+            _cancel: cancel,
+        }
+    }
+
+    /// Returns the result once it has arrived, None while the work is still underway.
+    pub fn poll(&mut self) -> Option<T> {
+        self.channel.try_recv().ok()
+    }
+}
+
+/// Gives up on the given work if it hasn't finished within the given amount of time.
+pub async fn deadline<T, F: Future<Output = api::APIResult<T>>>(
+    src: &str,
+    after: Duration,
+    work: F,
+) -> api::APIResult<T> {
+    tokio::time::timeout(after, work)
+        .await
+        .unwrap_or_else(|_| Err(api::APIError::timeout(src, after)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn hands_back_the_result() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let mut task = Task::spawn(async { 7 });
+            let result = loop {
+                match task.poll() {
+                    Some(result) => break result,
+                    None => tokio::time::delay_for(Duration::from_millis(10)).await,
+                }
+            };
+            assert_eq!(result, 7);
+        });
+    }
+
+    #[test]
+    fn dropping_cancels() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let finished = Arc::new(AtomicBool::new(false));
+            let flag = finished.clone();
+            let task = Task::spawn(async move {
+                tokio::time::delay_for(Duration::from_millis(100)).await;
+                flag.store(true, Ordering::SeqCst);
+            });
+            drop(task);
+            tokio::time::delay_for(Duration::from_millis(200)).await;
+            assert!(!finished.load(Ordering::SeqCst));
+        });
+    }
+}
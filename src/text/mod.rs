@@ -0,0 +1,229 @@
+use graphics::character::CharacterCache;
+use graphics::types::{Color, FontSize, Matrix2d};
+use graphics::{DrawState, Graphics, Transformed};
+
+static ELLIPSIS: char = '\u{2026}';
+
+/// A column of a fixed width that text gets wrapped into.
+///
+/// piston_window::text only ever draws a single line, so anything longer than the screen
+/// happily runs right off the edge of it. A TextBox measures each word using the glyph cache,
+/// breaks lines on whitespace (or mid-word if a single word simply will not fit) and, if the
+/// text needs more than max_lines lines, cuts it short with an ellipsis.
+///
+/// ```
+/// let subhead = TextBox::new(16, 480.0).max_lines(3);
+/// subhead.draw(WHITE, "Some rather long subhead...", &mut glyphs, c.transform, g)?;
+/// ```
+pub struct TextBox {
+    font_size: FontSize,
+    width: f64,
+    max_lines: usize,
+    line_height: f64,
+}
+
+impl TextBox {
+    pub fn new(font_size: FontSize, width: f64) -> TextBox {
+        TextBox {
+            font_size,
+            width,
+            max_lines: usize::MAX,
+            line_height: font_size as f64 * 1.5,
+        }
+    }
+
+    pub fn max_lines(mut self, max_lines: usize) -> TextBox {
+        self.max_lines = max_lines;
+        self
+    }
+
+    pub fn line_height(&self) -> f64 {
+        self.line_height
+    }
+
+    /// Breaks the given text up into lines that each fit within the width of this box.
+    /// Explicit newlines within the text are respected.
+    pub fn wrap<C: CharacterCache>(
+        &self,
+        cache: &mut C,
+        text: &str,
+    ) -> Result<Vec<String>, C::Error> {
+        let mut lines = vec![];
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if cache.width(self.font_size, &candidate)? <= self.width {
+                    line = candidate;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                // The word doesn't fit on a line of its own, so it gets broken up wherever it
+                // happens to hit the edge.
+                for ch in word.chars() {
+                    line.push(ch);
+                    if line.chars().count() > 1 && cache.width(self.font_size, &line)? > self.width
+                    {
+                        line.pop();
+                        lines.push(std::mem::take(&mut line));
+                        line.push(ch);
+                    }
+                }
+            }
+            lines.push(line);
+            if lines.len() > self.max_lines {
+                break;
+            }
+        }
+        if lines.len() > self.max_lines {
+            lines.truncate(self.max_lines);
+            if let Some(last) = lines.last_mut() {
+                *last = self.ellipsize(cache, last)?;
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Draws lines that were previously computed by TextBox::wrap. The transform is the
+    /// baseline of the first line.
+    pub fn draw_lines<C, G>(
+        &self,
+        color: Color,
+        lines: &[String],
+        cache: &mut C,
+        transform: Matrix2d,
+        g: &mut G,
+    ) -> Result<(), C::Error>
+    where
+        C: CharacterCache,
+        G: Graphics<Texture = <C as CharacterCache>::Texture>,
+    {
+        let text = graphics::Text::new_color(color, self.font_size);
+        for (index, line) in lines.iter().enumerate() {
+            text.draw(
+                line,
+                cache,
+                &DrawState::default(),
+                transform.trans(0.0, self.line_height * index as f64),
+                g,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Wraps and draws the given text, returning the height of the lines that were drawn.
+    pub fn draw<C, G>(
+        &self,
+        color: Color,
+        text: &str,
+        cache: &mut C,
+        transform: Matrix2d,
+        g: &mut G,
+    ) -> Result<f64, C::Error>
+    where
+        C: CharacterCache,
+        G: Graphics<Texture = <C as CharacterCache>::Texture>,
+    {
+        let lines = self.wrap(cache, text)?;
+        self.draw_lines(color, &lines, cache, transform, g)?;
+        Ok(self.line_height * lines.len() as f64)
+    }
+
+    // Chops characters off of the end of the line until there is room for an ellipsis.
+    fn ellipsize<C: CharacterCache>(&self, cache: &mut C, line: &str) -> Result<String, C::Error> {
+        let mut line = line.to_string();
+        loop {
+            let candidate = format!("{}{}", line.trim_end(), ELLIPSIS);
+            if line.is_empty() || cache.width(self.font_size, &candidate)? <= self.width {
+                return Ok(candidate);
+            }
+            line.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphics::character::Character;
+    use graphics::ImageSize;
+
+    struct Texture;
+
+    impl ImageSize for Texture {
+        fn get_size(&self) -> (u32, u32) {
+            (0, 0)
+        }
+    }
+
+    /// A monospaced stand-in for the real glyph cache where every character is 10.0 wide.
+    struct Monospace(Texture);
+
+    impl CharacterCache for Monospace {
+        type Texture = Texture;
+        type Error = ();
+
+        fn character<'a>(&'a mut self, _: FontSize, _: char) -> Result<Character<'a, Texture>, ()> {
+            Ok(Character {
+                offset: [0.0, 0.0],
+                advance_size: [10.0, 0.0],
+                atlas_offset: [0.0, 0.0],
+                atlas_size: [0.0, 0.0],
+                texture: &self.0,
+            })
+        }
+    }
+
+    fn wrap(width: f64, max_lines: usize, text: &str) -> Vec<String> {
+        TextBox::new(16, width)
+            .max_lines(max_lines)
+            .wrap(&mut Monospace(Texture), text)
+            .unwrap()
+    }
+
+    #[test]
+    fn short_text_is_untouched() {
+        assert_eq!(wrap(200.0, 3, "Orioles win"), vec!["Orioles win"]);
+    }
+
+    #[test]
+    fn wraps_on_whitespace() {
+        assert_eq!(
+            wrap(100.0, 10, "Machado, Schoop lift O's past Rays"),
+            vec!["Machado,", "Schoop", "lift O's", "past Rays"]
+        );
+    }
+
+    #[test]
+    fn breaks_words_that_do_not_fit() {
+        assert_eq!(
+            wrap(50.0, 10, "abcdefghijkl xy"),
+            vec!["abcde", "fghij", "kl xy"]
+        );
+    }
+
+    #[test]
+    fn respects_newlines() {
+        assert_eq!(wrap(100.0, 10, "one\n\ntwo"), vec!["one", "", "two"]);
+    }
+
+    #[test]
+    fn truncates_with_ellipsis() {
+        assert_eq!(
+            wrap(100.0, 2, "Machado, Schoop lift O's past Rays"),
+            vec!["Machado,", "Schoop\u{2026}"]
+        );
+        assert_eq!(wrap(50.0, 1, "abcde fghij"), vec!["abcd\u{2026}"]);
+    }
+
+    #[test]
+    fn empty_text_has_no_lines() {
+        assert!(wrap(100.0, 3, "").is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use crate::lineup::ImageKey;
+use image::RgbaImage;
+use piston_window::{G2dTexture, G2dTextureContext, Texture, TextureSettings};
+use std::collections::HashMap;
+
+/// Holds on to the GPU textures of every image that has been drawn so far.
+///
+/// Uploading an image to the GPU is far and away the most expensive thing that happens in any
+/// given frame, and yet the images themselves almost never change. So an image is uploaded
+/// the first time that its key is drawn and the resulting texture is reused from then on.
+pub struct TextureCache {
+    ctx: G2dTextureContext,
+    textures: HashMap<ImageKey, G2dTexture>,
+}
+
+impl TextureCache {
+    pub fn new(ctx: G2dTextureContext) -> TextureCache {
+        TextureCache {
+            ctx,
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Returns the texture for the given key, uploading the image if this is the first time
+    /// that the key has been seen.
+    pub fn get(&mut self, key: ImageKey, image: &RgbaImage) -> &G2dTexture {
+        let ctx = &mut self.ctx;
+        self.textures.entry(key).or_insert_with(|| {
+            Texture::from_image(ctx, image, &TextureSettings::new())
+                .unwrap_or_else(|err| panic!("Failed to upload texture for {:?}: {}", key, err))
+        })
+    }
+
+    /// Drops the texture of the given image, if it was ever uploaded. The image is uploaded
+    /// again should its key turn up after all.
+    pub fn forget(&mut self, key: ImageKey) {
+        self.textures.remove(&key);
+    }
+
+    /// Drops the textures of every downloaded photo. Photos are never shared between schedules,
+    /// so this is called whenever a new schedule replaces the old one.
+    pub fn forget_photos(&mut self) {
+        self.textures
+            .retain(|key, _| matches!(key, ImageKey::Asset(_)));
+    }
+}